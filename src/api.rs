@@ -1,3 +1,6 @@
 pub mod koreainvestapi;
 pub mod db_api;
+pub mod session;
+pub mod rate_limit;
 pub mod result;
+pub mod mock_kis;
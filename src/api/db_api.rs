@@ -1,13 +1,493 @@
-use crate::types::broker::Order;
+use crate::error::{ApiError, StockrsError};
+use crate::fee::FeeModel;
+use crate::price_db::PriceDB;
+use crate::time::{Clock, SimulatedClock};
+use crate::types::broker::{BrokerType, Order, OrderInquiry, OrderSide, OrderStatus, OrderType, ValidationError};
+use crate::types::market::Market;
+use crate::types::order_book::OrderBook;
+use crate::types::price::Bar;
+use crate::types::trading::{AssetInfo, Holding};
+use chrono::{Duration, NaiveDateTime, NaiveTime, Timelike};
+use std::collections::HashMap;
+use std::sync::Mutex;
 
-pub fn execute_order_from_db(_order: &Order) -> Result<String, Box<dyn std::error::Error>> {
-    todo!("execute order from db");
+/// 백테스트용 가상 계좌의 종목별 보유 정보
+///
+/// 전량 매도 후에도 평균매입가를 남겨 두어 매도 체결 기록 시 손익 계산에 사용합니다.
+struct SimHolding {
+    quantity: u32,
+    avg_price: f64,
 }
 
-pub fn check_fill_from_db(_order_id: &str) -> Result<bool, Box<dyn std::error::Error>> {
-    todo!("check fill from db");
+/// 접수되어 체결을 기다리는 가상 주문
+struct SimOrder {
+    stockcode: String,
+    side: OrderSide,
+    order_type: OrderType,
+    quantity: u32,
+    /// 지정가 (가격을 지정하지 않는 주문은 `None`, 최우선지정가는 첫 분봉 시가로 정해짐)
+    limit: Option<f64>,
+    filled_quantity: u32,
+    /// 누적 체결금액
+    filled_amount: f64,
+    cancelled: bool,
+    /// 접수 시각 (이후에 시작한 분봉부터 체결에 사용)
+    submitted_at: NaiveDateTime,
+    /// 마지막으로 체결 판단에 사용한 분봉 (같은 분봉의 거래량을 두 번 쓰지 않기 위함)
+    last_bar: Option<NaiveDateTime>,
+    /// 마지막으로 체결된 분봉의 시각
+    filled_at: Option<NaiveDateTime>,
 }
 
-pub fn cancel_order_from_db(_order_id: &str) -> Result<(), Box<dyn std::error::Error>> {
-    todo!("cancel order from db");
+struct SimAccount {
+    cash: f64,
+    holdings: HashMap<String, SimHolding>,
+    orders: HashMap<String, SimOrder>,
+    next_order_id: u64,
+}
+
+/// 과거 분봉 데이터에 대해 주문을 체결시키고 현금과 보유 종목을 내부에서 관리하는
+/// 백테스트용 API
+///
+/// 시각은 `Runner`와 같은 `SimulatedClock`을 공유하여 읽습니다.
+/// 체결 시 예수금에는 `FeeModel`로 계산한 수수료와 세금이 반영됩니다.
+/// `set_order_book`으로 호가를 넣어 두면 같은 분의 시장가 계열 주문은 호가를 따라 체결됩니다.
+pub struct DbApi {
+    prices: Mutex<PriceDB>,
+    clock: SimulatedClock,
+    fee_model: FeeModel,
+    account: Mutex<SimAccount>,
+    /// 종목별 최신 호가
+    order_books: Mutex<HashMap<String, OrderBook>>,
+}
+
+impl DbApi {
+    pub fn new(prices: PriceDB, clock: SimulatedClock, initial_cash: f64) -> Self {
+        let account = SimAccount {
+            cash: initial_cash,
+            holdings: HashMap::new(),
+            orders: HashMap::new(),
+            next_order_id: 1,
+        };
+        // 백테스트는 실전투자 수수료율 적용
+        let fee_model = FeeModel::for_broker(&BrokerType::REAL);
+        Self {
+            prices: Mutex::new(prices),
+            clock,
+            fee_model,
+            account: Mutex::new(account),
+            order_books: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 체결 시 적용할 거래비용 모델을 지정합니다. (기본값: `FeeModel::for_broker(&BrokerType::REAL)`)
+    pub fn with_fee_model(mut self, fee_model: FeeModel) -> Self {
+        self.fee_model = fee_model;
+        self
+    }
+
+    pub fn get_fee_model(&self) -> FeeModel { self.fee_model }
+
+    /// 종목의 상장 시장 (시세 DB에 없으면 KOSPI)
+    pub fn get_market(&self, stockcode: &str) -> Result<Market, StockrsError> {
+        self.prices.lock().unwrap().get_market(stockcode)
+    }
+
+    /// 체결 추정에 사용할 종목의 호가를 넣습니다. 같은 종목의 이전 호가는 교체됩니다.
+    pub fn set_order_book(&self, book: OrderBook) {
+        self.order_books.lock().unwrap().insert(book.get_stockcode().to_string(), book);
+    }
+
+    /// 현재 가상 시각이 속한 분봉의 시작 시각
+    fn current_minute(&self) -> NaiveDateTime {
+        let now = self.clock.now().naive_local();
+        now.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(now)
+    }
+
+    /// 주문을 접수하고 주문번호를 반환합니다.
+    pub fn execute_order(&self, order: &Order) -> Result<String, StockrsError> {
+        let submitted_at = self.now();
+        let mut account = self.account.lock().unwrap();
+        let order_id = format!("{:010}", account.next_order_id);
+        account.next_order_id += 1;
+        account.orders.insert(order_id.clone(), SimOrder {
+            stockcode: order.stockcode.clone(),
+            side: order.side,
+            order_type: order.order_type,
+            quantity: order.quantity,
+            limit: order.order_type.has_limit_price().then_some(order.price),
+            filled_quantity: 0,
+            filled_amount: 0.0,
+            cancelled: false,
+            submitted_at,
+            last_bar: None,
+            filled_at: None,
+        });
+        Ok(order_id)
+    }
+
+    /// 현재 가상 시각
+    pub fn now(&self) -> NaiveDateTime {
+        self.clock.now().naive_local()
+    }
+
+    /// 주문 체결 상태를 조회합니다.
+    ///
+    /// 미체결 잔량은 접수 이후에 시작한 분봉부터 현재 분봉까지 차례로 체결 여부가 결정됩니다.
+    /// (접수 시각에 이미 시작한 분봉은 전략이 주문할 때 알 수 없었던 시세이므로 쓰지 않음)
+    /// - 지정가 계열: 매수는 저가가 지정가 이하, 매도는 고가가 지정가 이상일 때 지정가로 체결
+    /// - 시장가/최유리지정가 계열: 분봉 시가로 체결
+    ///   (같은 분의 호가가 있으면 상대편 호가를 차례로 따라간 평균가로, 호가 잔량만큼 체결)
+    /// - 최우선지정가: 첫 분봉 시가를 지정가로 삼아 지정가와 같이 처리
+    /// - 조건부지정가: 장중에는 지정가, 15:20 종가 단일가매매부터는 시가로 체결
+    /// - IOC/FOK: 접수 후 첫 분봉에서만 체결을 시도하고 잔량은 취소 (FOK는 전량 체결될 때만 체결)
+    ///
+    /// 한 분봉에서 체결되는 수량은 그 분봉의 거래량을 넘지 않으므로 일부만 체결될 수 있습니다.
+    /// 예수금이나 보유 수량이 부족하면 체결되지 않고 다음 분봉에서 다시 시도합니다.
+    pub fn check_fill(&self, order_id: &str) -> Result<OrderInquiry, StockrsError> {
+        self.try_fill(order_id)?;
+        let account = self.account.lock().unwrap();
+        let order = account.orders.get(order_id).ok_or_else(|| ApiError::OrderNotFound(order_id.to_string()))?;
+        let status = if order.filled_quantity >= order.quantity {
+            OrderStatus::Filled
+        } else if order.cancelled {
+            OrderStatus::Cancelled
+        } else if order.filled_quantity > 0 {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::Submitted
+        };
+        let avg_price = match order.filled_quantity {
+            0 => 0.0,
+            filled => order.filled_amount / filled as f64,
+        };
+        Ok(OrderInquiry { status, filled_quantity: order.filled_quantity, avg_price, filled_at: order.filled_at })
+    }
+
+    fn try_fill(&self, order_id: &str) -> Result<(), StockrsError> {
+        let minute = self.current_minute();
+        let mut account = self.account.lock().unwrap();
+        loop {
+            let order = account.orders.get(order_id).ok_or_else(|| ApiError::OrderNotFound(order_id.to_string()))?;
+            if order.cancelled || order.filled_quantity >= order.quantity {
+                return Ok(());
+            }
+
+            let order_type = order.order_type;
+            let immediate = order_type.is_ioc() || order_type.is_fok();
+            let submitted_at = order.submitted_at;
+            let after = order.last_bar.map_or(submitted_at, |last| last.max(submitted_at));
+            let (bar, market) = {
+                let prices = self.prices.lock().unwrap();
+                (prices.get_next_minute_bar(&order.stockcode, after, minute)?, prices.get_market(&order.stockcode)?)
+            };
+            let bar = match bar {
+                Some(bar) => bar,
+                None => {
+                    // 즉시 체결 조건 주문은 접수 후 첫 분봉에 체결할 시세가 없으면 취소
+                    if immediate && minute > submitted_at && let Some(order) = account.orders.get_mut(order_id) {
+                        order.cancelled = true;
+                    }
+                    return Ok(());
+                }
+            };
+            let book = self.order_books.lock().unwrap()
+                .get(&order.stockcode)
+                .filter(|book| book.get_time() >= bar.get_time() && book.get_time() < bar.get_time() + Duration::minutes(1))
+                .cloned();
+            let quantity = self.fill_quantity(&mut account, order_id, &bar, book.as_ref(), market)?;
+            if let Some(order) = account.orders.get_mut(order_id) {
+                order.last_bar = Some(bar.get_time());
+                if (immediate && quantity == 0) || order_type.is_ioc() {
+                    order.cancelled = true;
+                }
+            }
+        }
+    }
+
+    /// 분봉 하나로 주문 잔량을 체결시키고 체결 수량을 반환합니다.
+    ///
+    /// `book`은 같은 분의 호가로, 있으면 시장가 계열 주문의 체결가와 체결 가능 수량을 호가에서 추정합니다.
+    fn fill_quantity(
+        &self,
+        account: &mut SimAccount,
+        order_id: &str,
+        bar: &Bar,
+        book: Option<&OrderBook>,
+        market: Market,
+    ) -> Result<u32, StockrsError> {
+        let order = account.orders.get_mut(order_id).ok_or_else(|| ApiError::OrderNotFound(order_id.to_string()))?;
+        if order.order_type == OrderType::PriorityLimit && order.limit.is_none() {
+            order.limit = Some(bar.get_open());
+        }
+
+        let remaining = order.quantity - order.filled_quantity;
+        let mut available = u32::try_from(bar.get_volume()).unwrap_or(u32::MAX);
+        let closing_auction = bar.get_time().time() >= NaiveTime::from_hms_opt(15, 20, 0).unwrap();
+        let price = match (order.order_type, order.limit) {
+            (OrderType::ConditionalLimit, _) if closing_auction => Some(bar.get_open()),
+            // 시가가 이미 지정가보다 유리하면 시가에, 장중에 지정가에 닿으면 지정가에 체결
+            (_, Some(limit)) => match order.side {
+                OrderSide::Buy if bar.get_open() <= limit => Some(bar.get_open()),
+                OrderSide::Sell if bar.get_open() >= limit => Some(bar.get_open()),
+                OrderSide::Buy => (bar.get_low() <= limit).then_some(limit),
+                OrderSide::Sell => (bar.get_high() >= limit).then_some(limit),
+            },
+            (_, None) => match book.and_then(|book| book.estimate_fill(order.side, remaining, None)) {
+                Some(estimate) => {
+                    available = estimate.quantity;
+                    Some(estimate.avg_price)
+                }
+                None => Some(bar.get_open()),
+            },
+        };
+        let price = match price {
+            Some(price) => price,
+            None => return Ok(0),
+        };
+
+        let stockcode = order.stockcode.clone();
+        let side = order.side;
+        let mut quantity = remaining.min(available);
+        if side == OrderSide::Sell {
+            let held = account.holdings.get(&stockcode).map(|h| h.quantity).unwrap_or(0);
+            quantity = quantity.min(held);
+        }
+        let order = account.orders.get(order_id).ok_or_else(|| ApiError::OrderNotFound(order_id.to_string()))?;
+        let fee = self.fee_model.fee(side, market, price, quantity, bar.get_time().date());
+        let amount = price * quantity as f64;
+        if quantity == 0
+            || (order.order_type.is_fok() && quantity < remaining)
+            || (side == OrderSide::Buy && account.cash < amount + fee)
+        {
+            return Ok(0);
+        }
+
+        match side {
+            OrderSide::Buy => {
+                account.cash -= amount + fee;
+                let holding = account.holdings.entry(stockcode).or_insert(SimHolding { quantity: 0, avg_price: 0.0 });
+                let total = holding.quantity + quantity;
+                holding.avg_price = (holding.avg_price * holding.quantity as f64 + amount) / total as f64;
+                holding.quantity = total;
+            }
+            OrderSide::Sell => {
+                account.cash += amount - fee;
+                if let Some(holding) = account.holdings.get_mut(&stockcode) {
+                    holding.quantity -= quantity;
+                }
+            }
+        }
+        if let Some(order) = account.orders.get_mut(order_id) {
+            order.filled_quantity += quantity;
+            order.filled_amount += amount;
+            order.filled_at = Some(bar.get_time());
+        }
+        Ok(quantity)
+    }
+
+    /// 원주문의 잔량을 취소하고 `amended`의 가격·수량으로 새 주문을 접수합니다.
+    ///
+    /// 실거래와 같이 정정 주문은 새 주문번호를 받으며, 정정 시각 이후에 시작한 분봉부터 체결됩니다.
+    pub fn amend_order(&self, order_id: &str, amended: &Order) -> Result<String, StockrsError> {
+        {
+            let mut account = self.account.lock().unwrap();
+            let order = account.orders.get_mut(order_id).ok_or_else(|| ApiError::OrderNotFound(order_id.to_string()))?;
+            if order.cancelled || order.filled_quantity >= order.quantity {
+                return Err(ValidationError::NotAmendable(order_id.to_string()).into());
+            }
+            order.cancelled = true;
+        }
+        self.execute_order(amended)
+    }
+
+    /// 미체결 잔량을 취소합니다. 이미 체결된 수량은 그대로 둡니다.
+    pub fn cancel_order(&self, order_id: &str) -> Result<(), StockrsError> {
+        let mut account = self.account.lock().unwrap();
+        let order = account.orders.get_mut(order_id).ok_or_else(|| ApiError::OrderNotFound(order_id.to_string()))?;
+        order.cancelled = true;
+        Ok(())
+    }
+
+    /// 예수금과 보유 종목을 현재 시각 전에 완성된 마지막 분봉의 종가로 평가한 총자산을 반환합니다.
+    pub fn get_asset_info(&self) -> Result<AssetInfo, StockrsError> {
+        let minute = self.current_minute();
+        let account = self.account.lock().unwrap();
+        let prices = self.prices.lock().unwrap();
+        let mut asset = account.cash;
+        for (stockcode, holding) in account.holdings.iter().filter(|(_, h)| h.quantity > 0) {
+            let price = prices.get_last_close(stockcode, minute)?.unwrap_or(holding.avg_price);
+            asset += price * holding.quantity as f64;
+        }
+        Ok(AssetInfo::new(self.clock.now().naive_local(), asset))
+    }
+
+    pub fn get_avg_price(&self, stockcode: &str) -> Result<f64, StockrsError> {
+        let account = self.account.lock().unwrap();
+        let holding = account.holdings.get(stockcode).ok_or_else(|| ApiError::StockNotFound(stockcode.to_string()))?;
+        Ok(holding.avg_price)
+    }
+
+    /// 보유 종목. 주문가능수량은 미체결 매도 주문의 잔량을 뺀 수량입니다.
+    pub fn get_holdings(&self) -> Result<Vec<Holding>, StockrsError> {
+        let account = self.account.lock().unwrap();
+        let mut holdings: Vec<Holding> = account.holdings.iter()
+            .filter(|(_, holding)| holding.quantity > 0)
+            .map(|(stockcode, holding)| {
+                let pending: u32 = account.orders.values()
+                    .filter(|order| order.side == OrderSide::Sell && !order.cancelled && &order.stockcode == stockcode)
+                    .map(|order| order.quantity - order.filled_quantity)
+                    .sum();
+                Holding::new(stockcode.clone(), holding.quantity, holding.avg_price)
+                    .with_orderable_quantity(holding.quantity.saturating_sub(pending))
+            })
+            .collect();
+        holdings.sort_by(|a, b| a.get_stockcode().cmp(b.get_stockcode()));
+        Ok(holdings)
+    }
+
+    /// 예수금 (백테스트는 체결 즉시 결제되므로 D+2 예수금과 같음)
+    pub fn get_cash(&self) -> f64 {
+        self.account.lock().unwrap().cash
+    }
+
+    /// 현재 가상 시각 전에 완성된 마지막 분봉의 종가 (완성된 분봉이 없으면 `ApiError::NoPrice`)
+    pub fn get_current_price(&self, stockcode: &str) -> Result<f64, StockrsError> {
+        let price = self.prices.lock().unwrap().get_last_close(stockcode, self.current_minute())?;
+        Ok(price.ok_or_else(|| ApiError::NoPrice(stockcode.to_string()))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::broker::test_order;
+    use chrono::{Local, NaiveDate, TimeZone};
+    use std::path::PathBuf;
+
+    fn api() -> DbApi {
+        let prices = PriceDB::new(PathBuf::from(":memory:")).unwrap();
+        for minute in 1..=2 {
+            let time = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, minute, 0).unwrap();
+            prices.upsert_minute_bar("005930", &Bar::new(time, 70200.0, 70500.0, 69800.0, 70000.0, 5)).unwrap();
+        }
+        let clock = SimulatedClock::new(Local.with_ymd_and_hms(2025, 7, 16, 9, 0, 30).unwrap());
+        DbApi::new(prices, clock, 10_000_000.0)
+    }
+
+    /// 09:00:30에 접수하고 09:01 분봉이 시작된 뒤 체결을 조회합니다.
+    fn submit(api: &DbApi, order: &Order) -> (String, OrderInquiry) {
+        api.clock.set(Local.with_ymd_and_hms(2025, 7, 16, 9, 0, 30).unwrap());
+        let id = api.execute_order(order).unwrap();
+        // 접수 시각에는 다음 분봉이 아직 없으므로 미체결
+        assert_eq!(api.check_fill(&id).unwrap().status, OrderStatus::Submitted);
+        api.clock.set(Local.with_ymd_and_hms(2025, 7, 16, 9, 1, 0).unwrap());
+        let inquiry = api.check_fill(&id).unwrap();
+        (id, inquiry)
+    }
+
+    #[test]
+    fn test_order_types() {
+        let api = api();
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 0, 30).unwrap();
+
+        // 시장가는 접수 후 첫 분봉의 시가로 체결
+        let (_, inquiry) = submit(&api, &test_order(date, OrderSide::Buy, OrderType::Market, 2, 0.0));
+        assert_eq!(inquiry.status, OrderStatus::Filled);
+        assert_eq!(inquiry.avg_price, 70200.0);
+        assert_eq!(inquiry.filled_at, NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 1, 0));
+
+        // 시가가 이미 지정가보다 낮으면 시가로 체결
+        assert_eq!(submit(&api, &test_order(date, OrderSide::Buy, OrderType::Limit, 1, 70500.0)).1.avg_price, 70200.0);
+
+        // 지정가가 저가보다 낮으면 미체결
+        assert_eq!(submit(&api, &test_order(date, OrderSide::Buy, OrderType::Limit, 1, 69000.0)).1.status, OrderStatus::Submitted);
+
+        // 거래량(5주)보다 큰 FOK는 전량 취소
+        let (_, inquiry) = submit(&api, &test_order(date, OrderSide::Buy, OrderType::LimitFok, 10, 70000.0));
+        assert_eq!((inquiry.status, inquiry.filled_quantity), (OrderStatus::Cancelled, 0));
+
+        // IOC는 가능한 수량만 체결하고 잔량 취소
+        let (_, inquiry) = submit(&api, &test_order(date, OrderSide::Buy, OrderType::LimitIoc, 10, 70000.0));
+        assert_eq!((inquiry.status, inquiry.filled_quantity), (OrderStatus::Cancelled, 5));
+        assert_eq!(inquiry.avg_price, 70000.0);
+    }
+
+    #[test]
+    fn test_market_order_walks_order_book() {
+        use crate::types::order_book::BookLevel;
+
+        let api = api();
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 0, 30).unwrap();
+        let at = |m| NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, m, 30).unwrap();
+        let asks = vec![BookLevel { price: 70100.0, quantity: 3 }, BookLevel { price: 70300.0, quantity: 10 }];
+
+        // 체결할 분봉과 다른 분의 호가는 쓰지 않고 시가로 체결
+        api.set_order_book(OrderBook::new("005930".to_string(), at(0), asks.clone(), Vec::new()));
+        assert_eq!(submit(&api, &test_order(date, OrderSide::Buy, OrderType::Market, 1, 0.0)).1.avg_price, 70200.0);
+
+        // 같은 분의 호가가 있으면 분봉 거래량(5주)이 아닌 호가 잔량을 따라 체결
+        api.set_order_book(OrderBook::new("005930".to_string(), at(1), asks, Vec::new()));
+        let (_, inquiry) = submit(&api, &test_order(date, OrderSide::Buy, OrderType::Market, 8, 0.0));
+        assert_eq!((inquiry.status, inquiry.filled_quantity), (OrderStatus::Filled, 8));
+        assert_eq!(inquiry.avg_price, (3.0 * 70100.0 + 5.0 * 70300.0) / 8.0);
+    }
+
+    #[test]
+    fn test_amend_order() {
+        let api = api();
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 0, 30).unwrap();
+        let (id, inquiry) = submit(&api, &test_order(date, OrderSide::Buy, OrderType::Limit, 3, 69000.0));
+        assert_eq!(inquiry.status, OrderStatus::Submitted);
+
+        let new_id = api.amend_order(&id, &test_order(date, OrderSide::Buy, OrderType::Limit, 2, 70000.0)).unwrap();
+        assert_ne!(id, new_id);
+        assert_eq!(api.check_fill(&id).unwrap().status, OrderStatus::Cancelled);
+        // 정정 시각에 이미 시작한 분봉에서는 체결되지 않음
+        assert_eq!(api.check_fill(&new_id).unwrap().status, OrderStatus::Submitted);
+
+        api.clock.set(Local.with_ymd_and_hms(2025, 7, 16, 9, 2, 0).unwrap());
+        let inquiry = api.check_fill(&new_id).unwrap();
+        assert_eq!((inquiry.status, inquiry.filled_quantity), (OrderStatus::Filled, 2));
+        assert!(api.amend_order(&new_id, &test_order(date, OrderSide::Buy, OrderType::Limit, 1, 70000.0)).is_err());
+    }
+
+    #[test]
+    fn test_holdings_exclude_pending_sells() {
+        let api = api();
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 0, 30).unwrap();
+        assert_eq!(submit(&api, &test_order(date, OrderSide::Buy, OrderType::Market, 2, 0.0)).1.status, OrderStatus::Filled);
+        assert!(api.get_cash() < 10_000_000.0 - 2.0 * 70200.0);
+
+        // 고가보다 높은 매도 지정가는 미체결로 남아 주문가능수량에서 빠짐
+        let sell = Order { side: OrderSide::Sell, ..test_order(date, OrderSide::Buy, OrderType::Limit, 1, 71000.0) };
+        let id = api.execute_order(&sell).unwrap();
+        assert_eq!(api.check_fill(&id).unwrap().status, OrderStatus::Submitted);
+        let holdings = api.get_holdings().unwrap();
+        assert_eq!((holdings[0].get_quantity(), holdings[0].get_orderable_quantity()), (2, 1));
+
+        api.cancel_order(&id).unwrap();
+        assert_eq!(api.get_holdings().unwrap()[0].get_orderable_quantity(), 2);
+        assert!(matches!(api.get_current_price("000660"), Err(StockrsError::Api(ApiError::NoPrice(_)))));
+    }
+
+    #[test]
+    fn test_prices_from_completed_bars() {
+        let prices = PriceDB::new(PathBuf::from(":memory:")).unwrap();
+        for (minute, close) in [(0, 70000.0), (1, 71000.0)] {
+            let time = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, minute, 0).unwrap();
+            prices.upsert_minute_bar("005930", &Bar::new(time, close, close, close, close, 5)).unwrap();
+        }
+        let clock = SimulatedClock::new(Local.with_ymd_and_hms(2025, 7, 16, 9, 0, 30).unwrap());
+        let api = DbApi::new(prices, clock.clone(), 0.0);
+        api.account.lock().unwrap().holdings.insert("005930".to_string(), SimHolding { quantity: 10, avg_price: 69000.0 });
+        // 진행 중인 분봉뿐이면 시세가 없고 자산은 평균매입가로 평가
+        assert!(api.get_current_price("005930").is_err());
+        assert_eq!(api.get_asset_info().unwrap().get_asset(), 690_000.0);
+
+        // 09:01 분봉은 진행 중이므로 완성된 09:00 분봉의 종가 사용
+        clock.set(Local.with_ymd_and_hms(2025, 7, 16, 9, 1, 30).unwrap());
+        assert_eq!(api.get_current_price("005930").unwrap(), 70000.0);
+        assert_eq!(api.get_asset_info().unwrap().get_asset(), 700_000.0);
+    }
 }
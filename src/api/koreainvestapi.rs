@@ -1,34 +1,477 @@
-use crate::types::api::{ApiEnv, Domestic006Result};
-use crate::types::broker::Order;
+use crate::error::{ApiError, ParseError, StockrsError};
+use crate::api::session::{session, KisConfig};
+use crate::types::api::{parse_field, string_field, ApiEnv, Domestic006Result};
+use crate::types::broker::{Order, OrderInquiry, OrderSide, OrderStatus};
+use crate::types::market::{Market, PriceInfo};
+use crate::types::price::Bar;
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
+use serde_json::{json, Value};
 
 
+/// 실전 거래 TR ID를 환경에 맞게 바꿉니다. (모의투자는 첫 글자가 `V`)
+fn trading_tr_id(real: &str, env: ApiEnv) -> String {
+    match env {
+        ApiEnv::Real => real.to_string(),
+        ApiEnv::Paper => format!("V{}", &real[1..]),
+    }
+}
+
+/// 연속조회 최대 페이지 수 (응답이 계속 다음 페이지를 가리킬 때의 안전장치)
+const MAX_PAGES: usize = 100;
 
 // 주식잔고조회[v1_국내주식-006]
 
+// input
+//  CTX_AREA_FK100
+//  연속조회검색조건100	String	Y	100	최초 조회시 공란, 다음 조회시 이전 응답의 값
+//  CTX_AREA_NK100
+//  연속조회키100	String	Y	100	최초 조회시 공란, 다음 조회시 이전 응답의 값
+
 // output1 Object Array
 //  pdno
 //  상품번호	String	Y	12	종목번호(뒷 6자리)
+//  hldg_qty
+//  보유수량	String	Y	19
+//  ord_psbl_qty
+//  주문가능수량	String	Y	10	매도 가능한 수량
 //  pchs_avg_pric
 //  매입평균가격	String	Y	22	매입금액 / 보유수량
+//  prpr
+//  현재가	String	Y	19
+//  evlu_amt
+//  평가금액	String	Y	19
+//  evlu_pfls_amt
+//  평가손익금액	String	Y	19
 
 // output2 Object Array
-// dnca_tot_amt
-// 예수금총금액	String	Y	19	예수금
+//  dnca_tot_amt
+//  예수금총금액	String	Y	19	예수금
 //  nass_amt
-//  순자산금액	String	Y	19	
+//  순자산금액	String	Y	19
+
+/// 주식잔고를 조회합니다. 보유 종목이 많으면 연속조회로 모든 페이지를 읽습니다.
+pub fn get_domestic006_result(env: ApiEnv) -> Result<Domestic006Result, StockrsError> {
+    let session = session(env)?;
+    let config = session.get_config();
+    let tr_id = trading_tr_id("TTTC8434R", env);
+
+    let mut output1: Vec<Value> = Vec::new();
+    let mut output2 = Value::Null;
+    let (mut ctx_fk, mut ctx_nk) = (String::new(), String::new());
+    let mut tr_cont = "";
+    for _ in 0..MAX_PAGES {
+        let query = [
+            ("CANO", config.cano.clone()),
+            ("ACNT_PRDT_CD", config.acnt_prdt_cd.clone()),
+            ("AFHR_FLPR_YN", "N".to_string()),
+            ("OFL_YN", String::new()),
+            ("INQR_DVSN", "02".to_string()),
+            ("UNPR_DVSN", "01".to_string()),
+            ("FUND_STTL_ICLD_YN", "N".to_string()),
+            ("FNCG_AMT_AUTO_RDPT_YN", "N".to_string()),
+            ("PRCS_DVSN", "00".to_string()),
+            ("CTX_AREA_FK100", ctx_fk.clone()),
+            ("CTX_AREA_NK100", ctx_nk.clone()),
+        ];
+        let response = session.get("/uapi/domestic-stock/v1/trading/inquire-balance", &tr_id, tr_cont, &query)?;
+        let body = response.body;
+        if let Some(rows) = body["output1"].as_array() {
+            output1.extend(rows.iter().cloned());
+        }
+        output2 = body["output2"].get(0).cloned().unwrap_or(Value::Null);
+
+        if !response.has_next {
+            break;
+        }
+        ctx_fk = body["ctx_area_fk100"].as_str().unwrap_or_default().trim().to_string();
+        ctx_nk = body["ctx_area_nk100"].as_str().unwrap_or_default().trim().to_string();
+        tr_cont = "N";
+    }
+
+    Domestic006Result::from_json(Local::now().naive_local(), &output1, &output2)
+}
+
+// 매수가능조회[v1_국내주식-007]
+
+// output Object
+//  ord_psbl_cash
+//  주문가능현금	String	Y	19
+//  nrcvb_buy_amt
+//  미수없는매수금액	String	Y	19	수수료 포함, 미수 없이 매수 가능한 금액
+
+pub fn get_buyable_cash(stockcode: &str, price: f64, env: ApiEnv) -> Result<f64, StockrsError> {
+    let session = session(env)?;
+    let config = session.get_config();
+    let query = [
+        ("CANO", config.cano.clone()),
+        ("ACNT_PRDT_CD", config.acnt_prdt_cd.clone()),
+        ("PDNO", stockcode.to_string()),
+        ("ORD_UNPR", format!("{}", price.round() as u64)),
+        ("ORD_DVSN", "00".to_string()),
+        ("CMA_EVLU_AMT_ICLD_YN", "N".to_string()),
+        ("OVRS_ICLD_YN", "N".to_string()),
+    ];
+    let response = session.get("/uapi/domestic-stock/v1/trading/inquire-psbl-order", &trading_tr_id("TTTC8908R", env), "", &query)?;
+    parse_field(&response.body["output"], "nrcvb_buy_amt")
+}
+
+// 주식현재가 시세[v1_국내주식-008] / 상품기본조회[v1_국내주식-029]
+
+// output Object
+//  stck_sdpr
+//  주식 기준가	String	Y	10	가격제한폭 계산 기준 (전일 종가)
+//  mket_id_cd
+//  시장ID코드	String	Y	3	STK: KOSPI, KSQ: KOSDAQ
+//  scty_grp_id_cd
+//  증권그룹ID코드	String	Y	2	EF: ETF (시장ID코드보다 우선)
+
+pub fn get_price_info(stockcode: &str, env: ApiEnv) -> Result<PriceInfo, StockrsError> {
+    let session = session(env)?;
+    let query = [
+        ("FID_COND_MRKT_DIV_CODE", "J".to_string()),
+        ("FID_INPUT_ISCD", stockcode.to_string()),
+    ];
+    let price = session.get("/uapi/domestic-stock/v1/quotations/inquire-price", "FHKST01010100", "", &query)?;
+    let prev_close: f64 = parse_field(&price.body["output"], "stck_sdpr")?;
+
+    let query = [
+        ("PRDT_TYPE_CD", "300".to_string()),
+        ("PDNO", stockcode.to_string()),
+    ];
+    let info = session.get("/uapi/domestic-stock/v1/quotations/search-stock-info", "CTPF1002R", "", &query)?;
+    let output = &info.body["output"];
+    let market = match (string_field(output, "scty_grp_id_cd").as_str(), string_field(output, "mket_id_cd").as_str()) {
+        ("EF", _) => Market::ETF,
+        (_, "KSQ") => Market::KOSDAQ,
+        (_, "STK") => Market::KOSPI,
+        _ => return Err(ApiError::StockNotFound(stockcode.to_string()).into()),
+    };
+    Ok(PriceInfo::new(market, prev_close))
+}
+
+// 주식현재가 시세[v1_국내주식-008]
+
+// output Object
+//  stck_prpr
+//  주식 현재가	String	Y	10
+
+pub fn get_current_price(stockcode: &str, env: ApiEnv) -> Result<f64, StockrsError> {
+    let query = [
+        ("FID_COND_MRKT_DIV_CODE", "J".to_string()),
+        ("FID_INPUT_ISCD", stockcode.to_string()),
+    ];
+    let price = session(env)?.get("/uapi/domestic-stock/v1/quotations/inquire-price", "FHKST01010100", "", &query)?;
+    parse_field(&price.body["output"], "stck_prpr")
+}
+
+// 주식주문(현금)[v1_국내주식-001]
+
+// input
+//  PDNO
+//  종목코드	String	Y	12
+//  ORD_DVSN
+//  주문구분	String	Y	2	OrderType::ord_dvsn() 참고
+//  ORD_QTY
+//  주문수량	String	Y	10
+//  ORD_UNPR
+//  주문단가	String	Y	19	지정가 계열이 아니면 "0"
+
+/// 주식주문(현금) 요청 본문의 종목·주문구분·수량·단가 항목
+pub fn order_params(order: &Order) -> Vec<(&'static str, String)> {
+    let price = match order.order_type.has_limit_price() {
+        true => format!("{}", order.price.round() as u64),
+        false => "0".to_string(),
+    };
+    vec![
+        ("PDNO", order.stockcode.clone()),
+        ("ORD_DVSN", order.order_type.ord_dvsn().to_string()),
+        ("ORD_QTY", order.quantity.to_string()),
+        ("ORD_UNPR", price),
+    ]
+}
 
-pub fn get_domestic006_result(_env: ApiEnv) -> Result<Domestic006Result, Box<dyn std::error::Error>> {
-    todo!("get domestic006 result");
+// output Object
+//  KRX_FWDG_ORD_ORGNO
+//  한국거래소전송주문조직번호	String	Y	5
+//  ODNO
+//  주문번호	String	Y	10
+
+/// 현금 주문을 제출하고 주문번호를 반환합니다.
+pub fn execute_order(order: &Order, env: ApiEnv) -> Result<String, StockrsError> {
+    let session = session(env)?;
+    let config = session.get_config();
+    let tr_id = match order.side {
+        OrderSide::Buy => trading_tr_id("TTTC0802U", env),
+        OrderSide::Sell => trading_tr_id("TTTC0801U", env),
+    };
+    let mut body = json!({
+        "CANO": config.cano,
+        "ACNT_PRDT_CD": config.acnt_prdt_cd,
+    });
+    for (key, value) in order_params(order) {
+        body[key] = Value::String(value);
+    }
+    let response = session.post("/uapi/domestic-stock/v1/trading/order-cash", &tr_id, &body)?;
+    order_number(&response.body)
 }
 
-pub fn execute_order(_order: &Order, _env: ApiEnv) -> Result<String, Box<dyn std::error::Error>> {
-    todo!("execute stock order");
+/// 주문 응답에서 주문번호(`ODNO`)를 읽습니다.
+fn order_number(body: &Value) -> Result<String, StockrsError> {
+    let odno = string_field(&body["output"], "ODNO");
+    match odno.is_empty() {
+        true => Err(ParseError::MissingField("ODNO".to_string()).into()),
+        false => Ok(odno),
+    }
 }
 
-pub fn check_fill(_order_id: &str, _env: ApiEnv) -> Result<bool, Box<dyn std::error::Error>> {
-    todo!("check order fill");
+// 주식일별주문체결조회[v1_국내주식-005]
+
+// output1 Object Array
+//  odno
+//  주문번호	String	Y	10
+//  ord_qty
+//  주문수량	String	Y	10
+//  tot_ccld_qty
+//  총체결수량	String	Y	10
+//  rmn_qty
+//  잔여수량	String	Y	10
+//  cncl_yn
+//  취소여부	String	Y	1
+//  rjct_qty
+//  거부수량	String	Y	10
+//  avg_prvs
+//  평균가	String	Y	22	체결평균가 ( 총체결금액 / 총체결수량 )
+//  ord_gno_brno
+//  주문채번지점번호	String	Y	5	정정/취소 시 KRX_FWDG_ORD_ORGNO
+//  ord_dvsn_cd
+//  주문구분코드	String	Y	2
+
+/// 당일 주문 중 `order_id`의 주문체결 내역을 조회합니다.
+fn inquire_order(order_id: &str, env: ApiEnv) -> Result<Value, StockrsError> {
+    let session = session(env)?;
+    let config = session.get_config();
+    let today = Local::now().format("%Y%m%d").to_string();
+    let query = [
+        ("CANO", config.cano.clone()),
+        ("ACNT_PRDT_CD", config.acnt_prdt_cd.clone()),
+        ("INQR_STRT_DT", today.clone()),
+        ("INQR_END_DT", today),
+        ("SLL_BUY_DVSN_CD", "00".to_string()),
+        ("INQR_DVSN", "00".to_string()),
+        ("PDNO", String::new()),
+        ("CCLD_DVSN", "00".to_string()),
+        ("ORD_GNO_BRNO", String::new()),
+        ("ODNO", order_id.to_string()),
+        ("INQR_DVSN_3", "00".to_string()),
+        ("INQR_DVSN_1", String::new()),
+        ("CTX_AREA_FK100", String::new()),
+        ("CTX_AREA_NK100", String::new()),
+    ];
+    let response = session.get("/uapi/domestic-stock/v1/trading/inquire-daily-ccld", &trading_tr_id("TTTC8001R", env), "", &query)?;
+    response.body["output1"]
+        .as_array()
+        .and_then(|rows| rows.iter().find(|row| string_field(row, "odno") == order_id))
+        .cloned()
+        .ok_or_else(|| ApiError::OrderNotFound(order_id.to_string()).into())
+}
+
+pub fn check_fill(order_id: &str, env: ApiEnv) -> Result<OrderInquiry, StockrsError> {
+    let row = inquire_order(order_id, env)?;
+    let ordered: u32 = parse_field(&row, "ord_qty")?;
+    let filled: u32 = parse_field(&row, "tot_ccld_qty")?;
+    let rejected: u32 = parse_field(&row, "rjct_qty")?;
+    let remaining: u32 = parse_field(&row, "rmn_qty")?;
+    let status = if filled >= ordered {
+        OrderStatus::Filled
+    } else if rejected > 0 {
+        OrderStatus::Rejected
+    } else if remaining == 0 || string_field(&row, "cncl_yn") == "Y" {
+        // 취소·정정은 별도 주문으로 접수되므로 원주문은 잔량이 0이 되는 것으로 판단
+        OrderStatus::Cancelled
+    } else if filled > 0 {
+        OrderStatus::PartiallyFilled
+    } else {
+        OrderStatus::Submitted
+    };
+    let avg_price = match filled {
+        0 => 0.0,
+        _ => parse_field(&row, "avg_prvs")?,
+    };
+    // 체결 통보시각은 체결이 있을 때만 채워짐
+    let filled_at = match string_field(&row, "infm_tmd") {
+        time if filled > 0 && !time.is_empty() => Some(NaiveDateTime::parse_from_str(
+            &format!("{}{}", string_field(&row, "ord_dt"), time),
+            "%Y%m%d%H%M%S",
+        )?),
+        _ => None,
+    };
+    Ok(OrderInquiry { status, filled_quantity: filled, avg_price, filled_at })
+}
+
+/// 원주문의 잔량을 모두 취소합니다. 이미 잔량이 없으면 아무 것도 하지 않습니다.
+pub fn cancel_order(order_id: &str, env: ApiEnv) -> Result<(), StockrsError> {
+    let row = inquire_order(order_id, env)?;
+    let remaining: u32 = parse_field(&row, "rmn_qty")?;
+    if remaining == 0 {
+        return Ok(());
+    }
+    let session = session(env)?;
+    let mut body = revise_cancel_body(order_id, &row, session.get_config());
+    body["RVSE_CNCL_DVSN_CD"] = json!("02");
+    body["ORD_DVSN"] = json!(string_field(&row, "ord_dvsn_cd"));
+    body["ORD_QTY"] = json!("0");
+    body["ORD_UNPR"] = json!("0");
+    body["QTY_ALL_ORD_YN"] = json!("Y");
+    session.post("/uapi/domestic-stock/v1/trading/order-rvsecncl", &trading_tr_id("TTTC0803U", env), &body)?;
+    Ok(())
+}
+
+// 주식주문(정정취소)[v1_국내주식-003]
+
+// input
+//  KRX_FWDG_ORD_ORGNO
+//  한국거래소전송주문조직번호	String	Y	5
+//  ORGN_ODNO
+//  원주문번호	String	Y	10
+//  ORD_DVSN
+//  주문구분	String	Y	2
+//  RVSE_CNCL_DVSN_CD
+//  정정취소구분코드	String	Y	2	01: 정정, 02: 취소
+//  ORD_QTY
+//  주문수량	String	Y	10	정정할 잔량 (QTY_ALL_ORD_YN = "N")
+//  ORD_UNPR
+//  주문단가	String	Y	19
+//  QTY_ALL_ORD_YN
+//  잔량전부주문여부	String	Y	1
+
+// output Object
+//  ODNO
+//  주문번호	String	Y	10	정정 주문의 새 주문번호
+
+/// 원주문의 잔량 중 `amended.quantity`만큼을 `amended.price`로 정정하고 새 주문번호를 반환합니다.
+pub fn amend_order(order_id: &str, amended: &Order, env: ApiEnv) -> Result<String, StockrsError> {
+    let row = inquire_order(order_id, env)?;
+    let session = session(env)?;
+    let mut body = revise_cancel_body(order_id, &row, session.get_config());
+    body["RVSE_CNCL_DVSN_CD"] = json!("01");
+    body["QTY_ALL_ORD_YN"] = json!("N");
+    for (key, value) in order_params(amended) {
+        // 종목은 원주문을 따름
+        if key != "PDNO" {
+            body[key] = Value::String(value);
+        }
+    }
+    let response = session.post("/uapi/domestic-stock/v1/trading/order-rvsecncl", &trading_tr_id("TTTC0803U", env), &body)?;
+    order_number(&response.body)
 }
 
-pub fn cancel_order(_order_id: &str, _env: ApiEnv) -> Result<(), Box<dyn std::error::Error>> {
-    todo!("cancel order");
+/// 주식주문(정정취소) 요청 본문의 계좌·원주문 항목
+fn revise_cancel_body(order_id: &str, row: &Value, config: &KisConfig) -> Value {
+    json!({
+        "CANO": config.cano,
+        "ACNT_PRDT_CD": config.acnt_prdt_cd,
+        "KRX_FWDG_ORD_ORGNO": string_field(row, "ord_gno_brno"),
+        "ORGN_ODNO": order_id,
+    })
+}
+
+// 주식당일분봉조회[v1_국내주식-022]
+
+// input
+//  FID_INPUT_HOUR_1
+//  입력시간1	String	Y	10	HHMMSS, 이 시각 이전 30개 분봉을 조회
+
+// output2 Object Array (최근 시각부터 내림차순)
+//  stck_bsop_date
+//  주식영업일자	String	Y	8
+//  stck_cntg_hour
+//  주식체결시간	String	Y	6
+//  stck_oprc / stck_hgpr / stck_lwpr / stck_prpr
+//  시가 / 고가 / 저가 / 현재가	String	Y	10
+//  cntg_vol
+//  체결거래량	String	Y	18
+
+/// 당일 `until` 이전의 분봉(최대 30개)을 시간 오름차순으로 조회합니다.
+pub fn get_minute_bars(stockcode: &str, until: NaiveTime, env: ApiEnv) -> Result<Vec<Bar>, StockrsError> {
+    let session = session(env)?;
+    let query = [
+        ("FID_ETC_CLS_CODE", String::new()),
+        ("FID_COND_MRKT_DIV_CODE", "J".to_string()),
+        ("FID_INPUT_ISCD", stockcode.to_string()),
+        ("FID_INPUT_HOUR_1", until.format("%H%M%S").to_string()),
+        ("FID_PW_DATA_INCU_YN", "N".to_string()),
+    ];
+    let response = session.get("/uapi/domestic-stock/v1/quotations/inquire-time-itemchartprice", "FHKST03010200", "", &query)?;
+    let mut bars = Vec::new();
+    for row in response.body["output2"].as_array().into_iter().flatten() {
+        let time = NaiveDateTime::parse_from_str(
+            &format!("{}{}", string_field(row, "stck_bsop_date"), string_field(row, "stck_cntg_hour")),
+            "%Y%m%d%H%M%S",
+        )?;
+        bars.push(Bar::new(
+            time,
+            parse_field(row, "stck_oprc")?,
+            parse_field(row, "stck_hgpr")?,
+            parse_field(row, "stck_lwpr")?,
+            parse_field(row, "stck_prpr")?,
+            parse_field(row, "cntg_vol")?,
+        ));
+    }
+    bars.sort_by_key(|bar| bar.get_time());
+    Ok(bars)
+}
+
+/// 기간별 시세 [v1_국내주식-016]
+///
+/// `from`부터 `to`까지의 일봉 중 최근 것부터 최대 100개를 수정주가로 조회하여 날짜 오름차순으로 반환합니다.
+/// 봉의 시각은 영업일의 자정입니다. 그 이전은 `to`를 받은 가장 이른 날짜의 전날로 다시 조회합니다.
+pub fn get_daily_bars(stockcode: &str, from: NaiveDate, to: NaiveDate, env: ApiEnv) -> Result<Vec<Bar>, StockrsError> {
+    let session = session(env)?;
+    let query = [
+        ("FID_COND_MRKT_DIV_CODE", "J".to_string()),
+        ("FID_INPUT_ISCD", stockcode.to_string()),
+        ("FID_INPUT_DATE_1", from.format("%Y%m%d").to_string()),
+        ("FID_INPUT_DATE_2", to.format("%Y%m%d").to_string()),
+        ("FID_PERIOD_DIV_CODE", "D".to_string()),
+        ("FID_ORG_ADJ_PRC", "0".to_string()),
+    ];
+    let response = session.get("/uapi/domestic-stock/v1/quotations/inquire-daily-itemchartprice", "FHKST03010100", "", &query)?;
+    let mut bars = Vec::new();
+    // 조회 구간에 영업일이 없으면 빈 행이 내려옴
+    for row in response.body["output2"].as_array().into_iter().flatten().filter(|row| !string_field(row, "stck_bsop_date").is_empty()) {
+        let date = NaiveDate::parse_from_str(&string_field(row, "stck_bsop_date"), "%Y%m%d")?;
+        bars.push(Bar::new(
+            date.and_time(NaiveTime::MIN),
+            parse_field(row, "stck_oprc")?,
+            parse_field(row, "stck_hgpr")?,
+            parse_field(row, "stck_lwpr")?,
+            parse_field(row, "stck_clpr")?,
+            parse_field(row, "acml_vol")?,
+        ));
+    }
+    bars.sort_by_key(|bar| bar.get_time());
+    Ok(bars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::broker::{test_order, OrderSide, OrderType};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_order_params_ord_dvsn() {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 1, 0).unwrap();
+        let params = order_params(&test_order(date, OrderSide::Buy, OrderType::Limit, 3, 70100.0));
+        assert!(params.contains(&("ORD_DVSN", "00".to_string())));
+        assert!(params.contains(&("ORD_UNPR", "70100".to_string())));
+
+        let params = order_params(&test_order(date, OrderSide::Buy, OrderType::MarketIoc, 3, 70100.0));
+        assert!(params.contains(&("ORD_DVSN", "13".to_string())));
+        assert!(params.contains(&("ORD_UNPR", "0".to_string())));
+
+        let params = order_params(&test_order(date, OrderSide::Buy, OrderType::LimitFok, 3, 70100.0));
+        assert!(params.contains(&("ORD_DVSN", "12".to_string())));
+        assert!(params.contains(&("ORD_UNPR", "70100".to_string())));
+    }
 }
@@ -0,0 +1,1027 @@
+use crate::api::session::KisConfig;
+use crate::error::{ApiError, StockrsError};
+use crate::types::market::Market;
+use chrono::{Duration, Local, NaiveDateTime};
+use log::{debug, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// 모의 서버가 발급하는 접근토큰
+const ACCESS_TOKEN: &str = "mock-access-token";
+/// 모의 서버가 발급하는 웹소켓 접속키
+const APPROVAL_KEY: &str = "mock-approval-key";
+/// 주문 응답의 한국거래소전송주문조직번호
+const ORDER_ORGNO: &str = "06010";
+/// 분봉조회 한 번에 돌려주는 분봉 수
+const MINUTE_BARS_PER_PAGE: usize = 30;
+/// 기간별 시세 한 번에 돌려주는 일봉 수
+const DAILY_BARS_PER_PAGE: usize = 100;
+
+/// 모의 서버의 초기 계좌·시세 상태
+///
+/// JSON 파일로 읽을 수 있으며, 빠진 항목은 기본값을 사용합니다.
+///
+/// ```json
+/// {
+///   "cash": 10000000,
+///   "holdings": [{ "stockcode": "000660", "quantity": 10, "avg_price": 200000 }],
+///   "stocks": [{ "stockcode": "005930", "market": "KOSPI", "prev_close": 70000 }],
+///   "minute_bars": [{ "stockcode": "005930", "date": "20250716", "time": "090100",
+///                     "open": 70000, "high": 70500, "low": 69900, "close": 70100, "volume": 1000 }],
+///   "daily_bars": [{ "stockcode": "005930", "date": "20250715",
+///                    "open": 69500, "high": 70200, "low": 69300, "close": 70000, "volume": 120000 }],
+///   "page_size": 50,
+///   "auto_fill": true
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MockFixtures {
+    /// 예수금
+    pub cash: f64,
+    pub holdings: Vec<MockHolding>,
+    pub stocks: Vec<MockStock>,
+    pub minute_bars: Vec<MockMinuteBar>,
+    pub daily_bars: Vec<MockDailyBar>,
+    /// 잔고조회 한 페이지의 종목 수 (연속조회 시험용)
+    pub page_size: usize,
+    /// 현재가로 체결 가능한 주문을 접수·정정·시세 변경 시 즉시 전량 체결할지 여부
+    pub auto_fill: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockHolding {
+    pub stockcode: String,
+    pub quantity: u32,
+    pub avg_price: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockStock {
+    pub stockcode: String,
+    #[serde(default)]
+    pub name: String,
+    pub market: Market,
+    /// 기준가 (전일 종가)
+    pub prev_close: f64,
+    /// 현재가 (없으면 기준가)
+    #[serde(default)]
+    pub price: Option<f64>,
+}
+
+/// 분봉 한 개 (`date`는 YYYYMMDD, `time`은 HHMMSS)
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockMinuteBar {
+    pub stockcode: String,
+    pub date: String,
+    pub time: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: u64,
+}
+
+/// 일봉 한 개 (`date`는 YYYYMMDD)
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockDailyBar {
+    pub stockcode: String,
+    pub date: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: u64,
+}
+
+impl Default for MockFixtures {
+    fn default() -> Self {
+        Self {
+            cash: 10_000_000.0,
+            holdings: Vec::new(),
+            stocks: Vec::new(),
+            minute_bars: Vec::new(),
+            daily_bars: Vec::new(),
+            page_size: 50,
+            auto_fill: true,
+        }
+    }
+}
+
+impl MockFixtures {
+    pub fn from_file(path: &Path) -> Result<Self, StockrsError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| StockrsError::Config(format!("{}: {}", path.display(), e)))?;
+        serde_json::from_str(&content)
+            .map_err(|e| StockrsError::Config(format!("{}: {}", path.display(), e)))
+    }
+}
+
+// ------------------------------------------------
+// 계좌 상태
+// ------------------------------------------------
+
+struct Stock {
+    name: String,
+    market: Market,
+    prev_close: f64,
+    price: f64,
+}
+
+struct Position {
+    quantity: u32,
+    avg_price: f64,
+}
+
+struct MockOrder {
+    stockcode: String,
+    buy: bool,
+    ord_dvsn: String,
+    quantity: u32,
+    price: f64,
+    filled: u32,
+    filled_amount: f64,
+    /// 취소·정정으로 빠져나간 수량
+    cancelled: u32,
+    /// 마지막 체결 시각
+    filled_at: Option<NaiveDateTime>,
+}
+
+impl MockOrder {
+    fn remaining(&self) -> u32 {
+        self.quantity - self.filled - self.cancelled
+    }
+
+    /// 지정가가 없는 주문구분 (`OrderType::has_limit_price` 참고)
+    fn is_limit(&self) -> bool {
+        matches!(self.ord_dvsn.as_str(), "00" | "02" | "11" | "12")
+    }
+}
+
+/// 주문 거부 등 업무 오류 응답 (`rt_cd` = "1")
+struct Rejection {
+    code: &'static str,
+    message: String,
+}
+
+fn reject(code: &'static str, message: impl Into<String>) -> Rejection {
+    Rejection { code, message: message.into() }
+}
+
+struct MockState {
+    cash: f64,
+    positions: BTreeMap<String, Position>,
+    stocks: HashMap<String, Stock>,
+    minute_bars: Vec<MockMinuteBar>,
+    daily_bars: Vec<MockDailyBar>,
+    orders: BTreeMap<String, MockOrder>,
+    next_order_no: u64,
+    page_size: usize,
+    auto_fill: bool,
+    /// 경로별 받은 요청 수
+    requests: HashMap<String, usize>,
+}
+
+impl MockState {
+    fn new(fixtures: MockFixtures) -> Self {
+        let stocks = fixtures.stocks.into_iter()
+            .map(|s| {
+                let price = s.price.unwrap_or(s.prev_close);
+                (s.stockcode, Stock { name: s.name, market: s.market, prev_close: s.prev_close, price })
+            })
+            .collect();
+        let positions = fixtures.holdings.into_iter()
+            .map(|h| (h.stockcode, Position { quantity: h.quantity, avg_price: h.avg_price }))
+            .collect();
+        Self {
+            cash: fixtures.cash,
+            positions,
+            stocks,
+            minute_bars: fixtures.minute_bars,
+            daily_bars: fixtures.daily_bars,
+            orders: BTreeMap::new(),
+            next_order_no: 1,
+            page_size: fixtures.page_size.max(1),
+            auto_fill: fixtures.auto_fill,
+            requests: HashMap::new(),
+        }
+    }
+
+    fn price(&self, stockcode: &str) -> Option<f64> {
+        self.stocks.get(stockcode).map(|s| s.price)
+    }
+
+    /// 미체결 매수 주문에 묶인 금액
+    fn reserved_cash(&self) -> f64 {
+        self.orders.values()
+            .filter(|o| o.buy)
+            .map(|o| {
+                let price = match o.is_limit() {
+                    true => o.price,
+                    false => self.price(&o.stockcode).unwrap_or(0.0),
+                };
+                o.remaining() as f64 * price
+            })
+            .sum()
+    }
+
+    /// 보유 수량에서 미체결 매도 주문 수량을 뺀 매도가능수량
+    fn sellable(&self, stockcode: &str) -> u32 {
+        let held = self.positions.get(stockcode).map_or(0, |p| p.quantity);
+        let pending: u32 = self.orders.values()
+            .filter(|o| !o.buy && o.stockcode == stockcode)
+            .map(|o| o.remaining())
+            .sum();
+        held.saturating_sub(pending)
+    }
+
+    fn order_no(&mut self) -> String {
+        let no = format!("{:010}", self.next_order_no);
+        self.next_order_no += 1;
+        no
+    }
+
+    fn place(&mut self, stockcode: &str, buy: bool, ord_dvsn: &str, quantity: u32, price: f64) -> Result<String, Rejection> {
+        let current = self.price(stockcode).ok_or_else(|| reject("APBK0656", "해당 종목정보가 없습니다."))?;
+        if quantity == 0 {
+            return Err(reject("APBK0918", "주문수량을 확인하여 주십시요."));
+        }
+        let order = MockOrder {
+            stockcode: stockcode.to_string(),
+            buy,
+            ord_dvsn: ord_dvsn.to_string(),
+            quantity,
+            price,
+            filled: 0,
+            filled_amount: 0.0,
+            cancelled: 0,
+            filled_at: None,
+        };
+        match buy {
+            true => {
+                let unit = match order.is_limit() {
+                    true => price,
+                    false => current,
+                };
+                if unit * quantity as f64 > self.cash - self.reserved_cash() {
+                    return Err(reject("APBK0952", "주문가능금액을 초과 했습니다."));
+                }
+            }
+            false => {
+                if quantity > self.sellable(stockcode) {
+                    return Err(reject("APBK0400", "주문 가능한 수량을 초과하였습니다."));
+                }
+            }
+        }
+        let order_no = self.order_no();
+        self.orders.insert(order_no.clone(), order);
+        if self.auto_fill {
+            self.match_orders();
+        }
+        Ok(order_no)
+    }
+
+    /// 현재가로 체결 가능한 미체결 주문을 현재가에 전량 체결합니다.
+    fn match_orders(&mut self) {
+        let executable: Vec<(String, u32, f64)> = self.orders.iter()
+            .filter(|(_, o)| o.remaining() > 0)
+            .filter_map(|(no, o)| {
+                let price = self.price(&o.stockcode)?;
+                let marketable = !o.is_limit() || (o.buy && o.price >= price) || (!o.buy && o.price <= price);
+                marketable.then(|| (no.clone(), o.remaining(), price))
+            })
+            .collect();
+        for (no, quantity, price) in executable {
+            let _ = self.fill(&no, quantity, price);
+        }
+    }
+
+    fn fill(&mut self, order_no: &str, quantity: u32, price: f64) -> Result<(), StockrsError> {
+        let order = self.orders.get_mut(order_no).ok_or_else(|| ApiError::OrderNotFound(order_no.to_string()))?;
+        let quantity = quantity.min(order.remaining());
+        let amount = price * quantity as f64;
+        order.filled += quantity;
+        order.filled_amount += amount;
+        order.filled_at = Some(Local::now().naive_local());
+
+        let position = self.positions.entry(order.stockcode.clone()).or_insert(Position { quantity: 0, avg_price: 0.0 });
+        match order.buy {
+            true => {
+                let cost = position.avg_price * position.quantity as f64 + amount;
+                position.quantity += quantity;
+                position.avg_price = cost / position.quantity as f64;
+                self.cash -= amount;
+            }
+            false => {
+                position.quantity -= quantity;
+                self.cash += amount;
+            }
+        }
+        if position.quantity == 0 {
+            self.positions.remove(&order.stockcode);
+        }
+        Ok(())
+    }
+
+    /// 원주문 잔량 중 `quantity`(없으면 전부)를 정정하거나 취소하고 새 주문번호를 반환합니다.
+    fn revise(&mut self, body: &Value) -> Result<String, Rejection> {
+        let origin = field(body, "ORGN_ODNO");
+        let order = match self.orders.get_mut(&origin) {
+            Some(order) if order.remaining() > 0 => order,
+            _ => return Err(reject("APBK0919", "정정/취소할 수량이 없습니다.")),
+        };
+        let remaining = order.remaining();
+        let quantity = match field(body, "QTY_ALL_ORD_YN").as_str() {
+            "Y" => remaining,
+            _ => field(body, "ORD_QTY").parse::<u32>().unwrap_or(0),
+        };
+        if quantity == 0 || quantity > remaining {
+            return Err(reject("APBK0918", "주문수량을 확인하여 주십시요."));
+        }
+
+        order.cancelled += quantity;
+        let (stockcode, buy) = (order.stockcode.clone(), order.buy);
+        match field(body, "RVSE_CNCL_DVSN_CD").as_str() {
+            // 정정: 빠져나간 수량으로 새 주문 접수
+            "01" => {
+                let price = field(body, "ORD_UNPR").parse::<f64>().unwrap_or(0.0);
+                let order_no = self.order_no();
+                let revised = MockOrder {
+                    stockcode,
+                    buy,
+                    ord_dvsn: field(body, "ORD_DVSN"),
+                    quantity,
+                    price,
+                    filled: 0,
+                    filled_amount: 0.0,
+                    cancelled: 0,
+                    filled_at: None,
+                };
+                self.orders.insert(order_no.clone(), revised);
+                if self.auto_fill {
+                    self.match_orders();
+                }
+                Ok(order_no)
+            }
+            _ => Ok(self.order_no()),
+        }
+    }
+}
+
+fn field(body: &Value, key: &str) -> String {
+    match &body[key] {
+        Value::String(s) => s.trim().to_string(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+// ------------------------------------------------
+// HTTP
+// ------------------------------------------------
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+    body: Value,
+}
+
+impl Request {
+    fn query(&self, key: &str) -> String {
+        self.query.get(key).cloned().unwrap_or_default()
+    }
+
+    fn header(&self, key: &str) -> &str {
+        self.headers.get(key).map(String::as_str).unwrap_or_default()
+    }
+}
+
+struct Response {
+    status: u16,
+    body: Value,
+    /// 연속조회 응답 헤더 (`F`/`M`: 다음 페이지 있음, `D`/`E`: 마지막 페이지)
+    tr_cont: Option<&'static str>,
+}
+
+impl Response {
+    fn ok(output: Value) -> Self {
+        let mut body = json!({ "rt_cd": "0", "msg_cd": "MCA00000", "msg1": "정상처리 되었습니다." });
+        if let (Value::Object(body), Value::Object(output)) = (&mut body, output) {
+            body.extend(output);
+        }
+        Self { status: 200, body, tr_cont: None }
+    }
+
+    fn rejected(rejection: Rejection) -> Self {
+        let body = json!({ "rt_cd": "1", "msg_cd": rejection.code, "msg1": rejection.message });
+        Self { status: 200, body, tr_cont: None }
+    }
+}
+
+fn url_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                match u8::from_str_radix(hex, 16) {
+                    Ok(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    Err(_) => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?.to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            headers.insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = headers.get("content-length").and_then(|v| v.parse::<usize>().ok()).unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+    let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let query = query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (url_decode(k), url_decode(v)))
+        .collect();
+    Some(Request { method, path: path.to_string(), query, headers, body })
+}
+
+fn write_response(mut stream: &TcpStream, response: Response) {
+    let body = response.body.to_string();
+    let reason = match response.status {
+        200 => "OK",
+        403 => "Forbidden",
+        404 => "Not Found",
+        _ => "Internal Server Error",
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\ncontent-type: application/json; charset=utf-8\r\ncontent-length: {}\r\nconnection: close\r\n",
+        response.status, reason, body.len(),
+    );
+    if let Some(tr_cont) = response.tr_cont {
+        head.push_str(&format!("tr_cont: {}\r\n", tr_cont));
+    }
+    head.push_str("\r\n");
+    let result = stream.write_all(head.as_bytes()).and_then(|_| stream.write_all(body.as_bytes()));
+    if let Err(e) = result {
+        warn!("모의 서버 응답 실패: {}", e);
+    }
+}
+
+// ------------------------------------------------
+// 엔드포인트
+// ------------------------------------------------
+
+fn handle(request: &Request, state: &Mutex<MockState>) -> Response {
+    debug!("모의 서버 요청 {} {} tr_id={}", request.method, request.path, request.header("tr_id"));
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/oauth2/tokenP") => Response {
+            status: 200,
+            body: json!({
+                "access_token": ACCESS_TOKEN,
+                "token_type": "Bearer",
+                "expires_in": 86_400,
+                "access_token_token_expired": (Local::now() + Duration::days(1)).format("%Y-%m-%d %H:%M:%S").to_string(),
+            }),
+            tr_cont: None,
+        },
+        ("POST", "/oauth2/Approval") => Response { status: 200, body: json!({ "approval_key": APPROVAL_KEY }), tr_cont: None },
+        (_, path) if path.starts_with("/uapi/") => {
+            if request.header("authorization") != format!("Bearer {}", ACCESS_TOKEN) {
+                return Response {
+                    status: 500,
+                    body: json!({ "rt_cd": "1", "msg_cd": "EGW00123", "msg1": "기간이 만료된 token 입니다." }),
+                    tr_cont: None,
+                };
+            }
+            let mut state = state.lock().unwrap();
+            *state.requests.entry(path.to_string()).or_default() += 1;
+            match path.trim_start_matches("/uapi/domestic-stock/v1") {
+                "/trading/inquire-balance" => balance(request, &state),
+                "/trading/inquire-psbl-order" => buyable(request, &state),
+                "/trading/order-cash" => order_cash(request, &mut state),
+                "/trading/inquire-daily-ccld" => daily_orders(request, &state),
+                "/trading/order-rvsecncl" => match state.revise(&request.body) {
+                    Ok(order_no) => Response::ok(json!({ "output": { "KRX_FWDG_ORD_ORGNO": ORDER_ORGNO, "ODNO": order_no } })),
+                    Err(rejection) => Response::rejected(rejection),
+                },
+                "/quotations/inquire-price" => quote(request, &state),
+                "/quotations/search-stock-info" => stock_info(request, &state),
+                "/quotations/inquire-time-itemchartprice" => minute_chart(request, &state),
+                "/quotations/inquire-daily-itemchartprice" => daily_chart(request, &state),
+                _ => not_found(path),
+            }
+        }
+        (_, path) => not_found(path),
+    }
+}
+
+fn not_found(path: &str) -> Response {
+    Response {
+        status: 404,
+        body: json!({ "rt_cd": "1", "msg_cd": "EGW00202", "msg1": format!("지원하지 않는 경로입니다: {}", path) }),
+        tr_cont: None,
+    }
+}
+
+fn balance(request: &Request, state: &MockState) -> Response {
+    let start = request.query("CTX_AREA_NK100").parse::<usize>().unwrap_or(0);
+    let rows: Vec<Value> = state.positions.iter()
+        .skip(start)
+        .take(state.page_size)
+        .map(|(code, p)| {
+            let price = state.price(code).unwrap_or(p.avg_price);
+            let name = state.stocks.get(code).map(|s| s.name.clone()).unwrap_or_default();
+            let pchs_amt = p.avg_price * p.quantity as f64;
+            let evlu_amt = price * p.quantity as f64;
+            let rate = match pchs_amt > 0.0 {
+                true => (evlu_amt - pchs_amt) / pchs_amt * 100.0,
+                false => 0.0,
+            };
+            json!({
+                "pdno": code,
+                "prdt_name": name,
+                "hldg_qty": p.quantity.to_string(),
+                "ord_psbl_qty": state.sellable(code).to_string(),
+                "pchs_avg_pric": format!("{:.4}", p.avg_price),
+                "pchs_amt": format!("{}", pchs_amt.round()),
+                "prpr": format!("{}", price),
+                "evlu_amt": format!("{}", evlu_amt.round()),
+                "evlu_pfls_amt": format!("{}", (evlu_amt - pchs_amt).round()),
+                "evlu_pfls_rt": format!("{:.2}", rate),
+            })
+        })
+        .collect();
+
+    let (pchs_total, evlu_total) = state.positions.iter().fold((0.0, 0.0), |(pchs, evlu), (code, p)| {
+        let price = state.price(code).unwrap_or(p.avg_price);
+        (pchs + p.avg_price * p.quantity as f64, evlu + price * p.quantity as f64)
+    });
+    let total = state.cash + evlu_total;
+    let next = start + rows.len();
+    let has_next = next < state.positions.len();
+    let mut response = Response::ok(json!({
+        "ctx_area_fk100": "",
+        "ctx_area_nk100": match has_next { true => next.to_string(), false => String::new() },
+        "output1": rows,
+        "output2": [{
+            "dnca_tot_amt": format!("{}", state.cash.round()),
+            "prvs_rcdl_excc_amt": format!("{}", state.cash.round()),
+            "scts_evlu_amt": format!("{}", evlu_total.round()),
+            "tot_evlu_amt": format!("{}", total.round()),
+            "nass_amt": format!("{}", total.round()),
+            "pchs_amt_smtl_amt": format!("{}", pchs_total.round()),
+            "evlu_pfls_smtl_amt": format!("{}", (evlu_total - pchs_total).round()),
+        }],
+    }));
+    response.tr_cont = Some(match has_next {
+        true => "M",
+        false => "D",
+    });
+    response
+}
+
+fn buyable(_request: &Request, state: &MockState) -> Response {
+    let cash = (state.cash - state.reserved_cash()).max(0.0).round();
+    Response::ok(json!({ "output": { "ord_psbl_cash": format!("{}", cash), "nrcvb_buy_amt": format!("{}", cash) } }))
+}
+
+fn order_cash(request: &Request, state: &mut MockState) -> Response {
+    let body = &request.body;
+    let buy = request.header("tr_id").ends_with("0802U");
+    let quantity = field(body, "ORD_QTY").parse::<u32>().unwrap_or(0);
+    let price = field(body, "ORD_UNPR").parse::<f64>().unwrap_or(0.0);
+    match state.place(&field(body, "PDNO"), buy, &field(body, "ORD_DVSN"), quantity, price) {
+        Ok(order_no) => Response::ok(json!({
+            "output": {
+                "KRX_FWDG_ORD_ORGNO": ORDER_ORGNO,
+                "ODNO": order_no,
+                "ORD_TMD": Local::now().format("%H%M%S").to_string(),
+            }
+        })),
+        Err(rejection) => Response::rejected(rejection),
+    }
+}
+
+fn daily_orders(request: &Request, state: &MockState) -> Response {
+    let odno = request.query("ODNO");
+    let rows: Vec<Value> = state.orders.iter()
+        .filter(|(no, _)| odno.is_empty() || **no == odno)
+        .map(|(no, o)| {
+            let avg = match o.filled {
+                0 => 0.0,
+                filled => o.filled_amount / filled as f64,
+            };
+            json!({
+                "ord_dt": Local::now().format("%Y%m%d").to_string(),
+                "ord_gno_brno": ORDER_ORGNO,
+                "odno": no,
+                "sll_buy_dvsn_cd": match o.buy { true => "02", false => "01" },
+                "pdno": o.stockcode,
+                "ord_dvsn_cd": o.ord_dvsn,
+                "ord_qty": o.quantity.to_string(),
+                "ord_unpr": format!("{}", o.price),
+                "tot_ccld_qty": o.filled.to_string(),
+                "tot_ccld_amt": format!("{}", o.filled_amount),
+                "avg_prvs": format!("{}", avg),
+                "rmn_qty": o.remaining().to_string(),
+                "rjct_qty": "0",
+                "cncl_yn": match o.cancelled > 0 && o.remaining() == 0 { true => "Y", false => "N" },
+                "infm_tmd": o.filled_at.map(|t| t.format("%H%M%S").to_string()).unwrap_or_default(),
+            })
+        })
+        .collect();
+    Response::ok(json!({ "ctx_area_fk100": "", "ctx_area_nk100": "", "output1": rows, "output2": {} }))
+}
+
+fn quote(request: &Request, state: &MockState) -> Response {
+    match state.stocks.get(&request.query("FID_INPUT_ISCD")) {
+        Some(stock) => Response::ok(json!({
+            "output": {
+                "stck_prpr": format!("{}", stock.price),
+                "stck_sdpr": format!("{}", stock.prev_close),
+                "prdy_vrss": format!("{}", stock.price - stock.prev_close),
+            }
+        })),
+        None => Response::rejected(reject("APBK0656", "해당 종목정보가 없습니다.")),
+    }
+}
+
+fn stock_info(request: &Request, state: &MockState) -> Response {
+    match state.stocks.get(&request.query("PDNO")) {
+        Some(stock) => {
+            let (mket_id_cd, scty_grp_id_cd) = match stock.market {
+                Market::KOSPI => ("STK", "ST"),
+                Market::KOSDAQ => ("KSQ", "ST"),
+                Market::ETF => ("STK", "EF"),
+            };
+            Response::ok(json!({
+                "output": { "prdt_abrv_name": stock.name, "mket_id_cd": mket_id_cd, "scty_grp_id_cd": scty_grp_id_cd }
+            }))
+        }
+        None => Response::rejected(reject("APBK0656", "해당 종목정보가 없습니다.")),
+    }
+}
+
+fn minute_chart(request: &Request, state: &MockState) -> Response {
+    let stockcode = request.query("FID_INPUT_ISCD");
+    let until = request.query("FID_INPUT_HOUR_1");
+    let mut bars: Vec<&MockMinuteBar> = state.minute_bars.iter()
+        .filter(|b| b.stockcode == stockcode && b.time <= until)
+        .collect();
+    // 가장 최근 영업일의 분봉만 조회
+    let last_date = bars.iter().map(|b| b.date.clone()).max().unwrap_or_default();
+    bars.retain(|b| b.date == last_date);
+    bars.sort_by(|a, b| b.time.cmp(&a.time));
+    let rows: Vec<Value> = bars.into_iter()
+        .take(MINUTE_BARS_PER_PAGE)
+        .map(|b| json!({
+            "stck_bsop_date": b.date,
+            "stck_cntg_hour": b.time,
+            "stck_oprc": format!("{}", b.open),
+            "stck_hgpr": format!("{}", b.high),
+            "stck_lwpr": format!("{}", b.low),
+            "stck_prpr": format!("{}", b.close),
+            "cntg_vol": b.volume.to_string(),
+        }))
+        .collect();
+    let price = state.price(&stockcode).unwrap_or(0.0);
+    Response::ok(json!({ "output1": { "stck_prpr": format!("{}", price) }, "output2": rows }))
+}
+
+fn daily_chart(request: &Request, state: &MockState) -> Response {
+    let stockcode = request.query("FID_INPUT_ISCD");
+    let (from, to) = (request.query("FID_INPUT_DATE_1"), request.query("FID_INPUT_DATE_2"));
+    let mut bars: Vec<&MockDailyBar> = state.daily_bars.iter()
+        .filter(|b| b.stockcode == stockcode && from <= b.date && b.date <= to)
+        .collect();
+    bars.sort_by(|a, b| b.date.cmp(&a.date));
+    let rows: Vec<Value> = bars.into_iter()
+        .take(DAILY_BARS_PER_PAGE)
+        .map(|b| json!({
+            "stck_bsop_date": b.date,
+            "stck_oprc": format!("{}", b.open),
+            "stck_hgpr": format!("{}", b.high),
+            "stck_lwpr": format!("{}", b.low),
+            "stck_clpr": format!("{}", b.close),
+            "acml_vol": b.volume.to_string(),
+        }))
+        .collect();
+    Response::ok(json!({ "output1": {}, "output2": rows }))
+}
+
+// ------------------------------------------------
+// 서버
+// ------------------------------------------------
+
+/// 오프라인 시험용 KIS REST 모의 서버
+///
+/// 토큰 발급, 잔고·매수가능·주문체결 조회, 현금주문, 정정/취소, 현재가·종목정보·당일분봉·기간별시세 조회를
+/// 메모리의 계좌 상태로 처리합니다. 수수료와 세금은 계산하지 않습니다.
+/// `get_config`로 만든 설정을 `session::install`에 넘기면 KIS 클라이언트가 이 서버를 사용합니다.
+/// 값이 삭제되면 서버도 종료됩니다.
+pub struct MockKisServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockKisServer {
+    /// 임의의 로컬 포트에서 서버를 시작합니다.
+    pub fn start(fixtures: MockFixtures) -> Result<Self, StockrsError> {
+        Self::bind("127.0.0.1:0", fixtures)
+    }
+
+    pub fn bind(addr: &str, fixtures: MockFixtures) -> Result<Self, StockrsError> {
+        let listener = TcpListener::bind(addr).map_err(|e| ApiError::Network(e.to_string()))?;
+        let addr = listener.local_addr().map_err(|e| ApiError::Network(e.to_string()))?;
+        let state = Arc::new(Mutex::new(MockState::new(fixtures)));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let state = state.clone();
+            let shutdown = shutdown.clone();
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    if let Some(request) = read_request(&stream) {
+                        write_response(&stream, handle(&request, &state));
+                    }
+                }
+            })
+        };
+        Ok(Self { addr, state, shutdown, handle: Some(handle) })
+    }
+
+    pub fn get_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// 이 서버에 접속하는 세션 설정 (토큰 캐시는 임시 디렉터리에 포트별로 저장)
+    pub fn get_config(&self) -> KisConfig {
+        KisConfig {
+            app_key: "mock-app-key".to_string(),
+            app_secret: "mock-app-secret".to_string(),
+            cano: "50000000".to_string(),
+            acnt_prdt_cd: "01".to_string(),
+            rest_url: Some(self.get_url()),
+            token_cache: Some(std::env::temp_dir().join(format!("stockrs_mock_kis_{}.json", self.addr.port()))),
+            ws_url: None,
+            hts_id: None,
+            // 로컬 서버이므로 KIS 호출 한도를 적용하지 않음
+            requests_per_second: Some(1000),
+        }
+    }
+
+    pub fn get_cash(&self) -> f64 {
+        self.state.lock().unwrap().cash
+    }
+
+    /// 보유 수량 (미보유면 0)
+    pub fn get_quantity(&self, stockcode: &str) -> u32 {
+        self.state.lock().unwrap().positions.get(stockcode).map_or(0, |p| p.quantity)
+    }
+
+    /// `path`(예: `/uapi/domestic-stock/v1/trading/inquire-balance`)로 받은 요청 수
+    pub fn get_request_count(&self, path: &str) -> usize {
+        self.state.lock().unwrap().requests.get(path).copied().unwrap_or(0)
+    }
+
+    /// 현재가를 바꿉니다. `auto_fill`이면 체결 가능해진 미체결 주문을 체결합니다.
+    pub fn set_price(&self, stockcode: &str, price: f64) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(stock) = state.stocks.get_mut(stockcode) {
+            stock.price = price;
+        }
+        if state.auto_fill {
+            state.match_orders();
+        }
+    }
+
+    /// 미체결 주문의 일부 또는 전부를 지정한 가격에 체결합니다. (잔량을 넘는 수량은 잔량까지만 체결)
+    pub fn fill(&self, order_id: &str, quantity: u32, price: f64) -> Result<(), StockrsError> {
+        self.state.lock().unwrap().fill(order_id, quantity, price)
+    }
+
+    /// 이 서버를 `env` 세션으로 등록합니다. (테스트용)
+    ///
+    /// 세션은 프로세스 전역이므로, 반환된 가드를 가진 동안 다른 테스트가 세션을 교체하지 못하게 합니다.
+    #[cfg(test)]
+    pub(crate) fn install(&self, env: crate::types::api::ApiEnv) -> std::sync::MutexGuard<'static, ()> {
+        static SESSION_LOCK: Mutex<()> = Mutex::new(());
+        // 앞선 테스트가 실패해 잠금이 오염되어도 다음 테스트는 진행
+        let guard = SESSION_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        crate::api::session::install(env, self.get_config());
+        guard
+    }
+
+    /// 서버 스레드가 끝날 때까지 대기합니다. (바이너리용)
+    pub fn wait(mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for MockKisServer {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.shutdown.store(true, Ordering::SeqCst);
+            // accept 대기를 깨우기 위한 접속
+            let _ = TcpStream::connect(self.addr);
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::koreainvestapi::{amend_order, cancel_order, check_fill, execute_order, get_buyable_cash, get_domestic006_result, get_daily_bars, get_minute_bars, get_price_info};
+    use crate::api::session;
+    use crate::broker::PaperBroker;
+    use crate::data_reader::make_data_reader;
+    use crate::db_manager::DBManager;
+    use crate::types::api::ApiEnv;
+    use crate::types::broker::{test_order, Broker, OrderSide, OrderStatus, OrderType};
+    use crate::types::data_reader::DataReaderType;
+    use chrono::{NaiveDate, NaiveTime};
+    use std::path::PathBuf;
+
+    fn stock(stockcode: &str, market: Market, prev_close: f64) -> MockStock {
+        MockStock { stockcode: stockcode.to_string(), name: String::new(), market, prev_close, price: None }
+    }
+
+    fn holding(stockcode: &str, quantity: u32, avg_price: f64) -> MockHolding {
+        MockHolding { stockcode: stockcode.to_string(), quantity, avg_price }
+    }
+
+    fn bar(time: &str, close: f64) -> MockMinuteBar {
+        MockMinuteBar {
+            stockcode: "005930".to_string(),
+            date: "20250716".to_string(),
+            time: time.to_string(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 100,
+        }
+    }
+
+    #[test]
+    fn test_paper_flow() {
+        let server = MockKisServer::start(MockFixtures {
+            cash: 1_000_000.0,
+            holdings: vec![holding("000660", 10, 200_000.0), holding("069500", 5, 35_000.0)],
+            stocks: vec![
+                stock("005930", Market::KOSPI, 70_000.0),
+                stock("000660", Market::KOSPI, 210_000.0),
+                stock("069500", Market::ETF, 35_000.0),
+            ],
+            minute_bars: vec![bar("090100", 70_100.0), bar("090000", 70_000.0), bar("090200", 70_200.0)],
+            daily_bars: ["20250714", "20250715"].iter()
+                .map(|date| MockDailyBar { stockcode: "005930".to_string(), date: date.to_string(), open: 69_000.0, high: 70_500.0, low: 68_500.0, close: 70_000.0, volume: 100 })
+                .collect(),
+            page_size: 1,
+            ..MockFixtures::default()
+        }).unwrap();
+        let _session = server.install(ApiEnv::Paper);
+
+        // 한 페이지에 한 종목씩 연속조회
+        let balance = get_domestic006_result(ApiEnv::Paper).unwrap();
+        assert_eq!(balance.get_holdings().len(), 2);
+        assert_eq!(balance.get_ord_psbl_qty("069500"), 5);
+        assert_eq!(get_price_info("069500", ApiEnv::Paper).unwrap().get_market(), Market::ETF);
+
+        let bars = get_minute_bars("005930", NaiveTime::from_hms_opt(9, 1, 0).unwrap(), ApiEnv::Paper).unwrap();
+        assert_eq!(bars.iter().map(|b| b.get_close()).collect::<Vec<_>>(), vec![70_000.0, 70_100.0]);
+        let july = |day| NaiveDate::from_ymd_opt(2025, 7, day).unwrap();
+        let daily = get_daily_bars("005930", july(15), july(16), ApiEnv::Paper).unwrap();
+        assert_eq!(daily.iter().map(|b| b.get_time().date()).collect::<Vec<_>>(), vec![july(15)]);
+
+        // 검증 → 주문 → 즉시 체결 → 거래 기록
+        let db = DBManager::new(PathBuf::from(":memory:"), DataReaderType::PAPER).unwrap();
+        let broker = PaperBroker::new();
+        let order_id = broker.execute(&test_order(Local::now().naive_local(), OrderSide::Buy, OrderType::Limit, 10, 70_000.0), &db).unwrap();
+        assert_eq!(broker.status(&order_id), Some(OrderStatus::Filled));
+        assert_eq!(server.get_cash(), 300_000.0);
+        assert_eq!(server.get_quantity("005930"), 10);
+        assert_eq!(db.holdings().unwrap().len(), 1);
+
+        // overview 갱신은 한 번 조회한 잔고를 재사용하고, 체결이 보고되면 다시 조회
+        let balance_count = || server.get_request_count("/uapi/domestic-stock/v1/trading/inquire-balance");
+        let before = balance_count();
+        db.update_overview().unwrap();
+        db.update_overview().unwrap();
+        // 한 페이지에 한 종목씩 3종목
+        assert_eq!(balance_count(), before + 3);
+        db.invalidate_account();
+        db.update_overview().unwrap();
+        assert_eq!(balance_count(), before + 6);
+
+        // 보유 종목·예수금은 같은 잔고조회로, 현재가는 시세조회로 읽음
+        let reader = make_data_reader(DataReaderType::PAPER);
+        let codes: Vec<String> = reader.get_holdings().unwrap().iter().map(|h| h.get_stockcode().to_string()).collect();
+        assert_eq!(codes, vec!["000660", "005930", "069500"]);
+        assert_eq!(reader.get_orderable_cash().unwrap(), 300_000.0);
+        assert_eq!(balance_count(), before + 9);
+        assert_eq!(reader.get_current_price("005930").unwrap(), 70_000.0);
+
+        // 주문가능금액 초과는 제출 전에 거부
+        assert!(broker.execute(&test_order(Local::now().naive_local(), OrderSide::Buy, OrderType::Limit, 10, 70_000.0), &db).is_err());
+    }
+
+    #[test]
+    fn test_resting_order_amend_cancel() {
+        let server = MockKisServer::start(MockFixtures {
+            cash: 1_000_000.0,
+            stocks: vec![stock("005930", Market::KOSPI, 70_000.0)],
+            auto_fill: false,
+            ..MockFixtures::default()
+        }).unwrap();
+        let _session = server.install(ApiEnv::Real);
+        let env = ApiEnv::Real;
+
+        let order_id = execute_order(&test_order(Local::now().naive_local(), OrderSide::Buy, OrderType::Limit, 10, 70_000.0), env).unwrap();
+        assert_eq!(check_fill(&order_id, env).unwrap().status, OrderStatus::Submitted);
+        assert_eq!(get_buyable_cash("005930", 70_000.0, env).unwrap(), 300_000.0);
+
+        server.fill(&order_id, 3, 70_000.0).unwrap();
+        let inquiry = check_fill(&order_id, env).unwrap();
+        assert_eq!((inquiry.status, inquiry.filled_quantity, inquiry.avg_price), (OrderStatus::PartiallyFilled, 3, 70_000.0));
+
+        // 잔량 7주 중 4주를 정정하고 남은 3주는 취소
+        let amended = amend_order(&order_id, &test_order(Local::now().naive_local(), OrderSide::Buy, OrderType::Limit, 4, 69_900.0), env).unwrap();
+        assert_eq!(check_fill(&amended, env).unwrap().status, OrderStatus::Submitted);
+        cancel_order(&order_id, env).unwrap();
+        assert_eq!(check_fill(&order_id, env).unwrap().status, OrderStatus::Cancelled);
+
+        cancel_order(&amended, env).unwrap();
+        // 잔량이 없는 주문의 취소는 무시
+        cancel_order(&amended, env).unwrap();
+        assert_eq!(get_buyable_cash("005930", 70_000.0, env).unwrap(), 790_000.0);
+    }
+
+    #[test]
+    fn test_rejected_token_is_reissued() {
+        let server = MockKisServer::start(MockFixtures {
+            stocks: vec![stock("005930", Market::KOSPI, 70_000.0)],
+            ..MockFixtures::default()
+        }).unwrap();
+        // 만료 전이지만 서버가 거부하는 토큰이 캐시에 있음
+        let config = server.get_config();
+        let expires_at = (Local::now() + Duration::hours(10)).format("%Y-%m-%d %H:%M:%S").to_string();
+        let cache = json!({ "access_token": { "value": "revoked", "expires_at": expires_at }, "approval_key": null });
+        std::fs::write(config.token_cache.as_ref().unwrap(), cache.to_string()).unwrap();
+
+        let session = session::KisSession::new(ApiEnv::Real, config);
+        let query = [("FID_COND_MRKT_DIV_CODE", "J".to_string()), ("FID_INPUT_ISCD", "005930".to_string())];
+        let response = session.get("/uapi/domestic-stock/v1/quotations/inquire-price", "FHKST01010100", "", &query).unwrap();
+        assert_eq!(response.body["output"]["stck_prpr"], "70000");
+        assert_eq!(session.access_token().unwrap(), ACCESS_TOKEN);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::error::{ApiError, StockrsError};
+use crate::types::api::ApiEnv;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 실전투자 초당 거래건수 한도
+const REAL_REQUESTS_PER_SECOND: u32 = 20;
+/// 모의투자 초당 거래건수 한도
+const PAPER_REQUESTS_PER_SECOND: u32 = 2;
+
+/// 초당 요청 수를 제한하는 토큰 버킷
+///
+/// 토큰은 초당 `rate`개씩 최대 `burst`개까지 쌓이며, 요청마다 한 개를 사용합니다.
+/// 토큰이 없으면 다음 토큰이 생길 때까지 기다립니다. 여러 스레드가 함께 사용할 수 있습니다.
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    /// (남은 토큰, 마지막으로 토큰을 채운 시각)
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    pub fn new(rate: u32, burst: u32) -> Self {
+        let burst = burst.max(1) as f64;
+        Self { rate: rate.max(1) as f64, burst, state: Mutex::new((burst, Instant::now())) }
+    }
+
+    /// 환경별 초당 거래건수 한도. 1초 경계에서 한도를 넘지 않도록 한 번에 한 건씩 고르게 보냅니다.
+    pub fn for_env(env: ApiEnv) -> Self {
+        match env {
+            ApiEnv::Real => Self::new(REAL_REQUESTS_PER_SECOND, 1),
+            ApiEnv::Paper => Self::new(PAPER_REQUESTS_PER_SECOND, 1),
+        }
+    }
+
+    /// 토큰 한 개를 사용합니다. 토큰이 없으면 생길 때까지 기다립니다.
+    pub fn acquire(&self) {
+        let wait = {
+            let mut state = self.state.lock().unwrap();
+            let (tokens, last) = *state;
+            let now = Instant::now();
+            let tokens = (tokens + now.duration_since(last).as_secs_f64() * self.rate).min(self.burst);
+            // 부족한 토큰은 미리 사용한 것으로 기록하여 기다리는 동안 다른 요청이 끼어들지 않게 함
+            *state = (tokens - 1.0, now);
+            match tokens >= 1.0 {
+                true => Duration::ZERO,
+                false => Duration::from_secs_f64((1.0 - tokens) / self.rate),
+            }
+        };
+        thread::sleep(wait);
+    }
+}
+
+/// 실패한 요청을 다시 보낼지와 대기 시간을 정합니다.
+///
+/// 대기 시간은 `base_delay`부터 재시도마다 두 배씩 늘어나며 `max_delay`를 넘지 않습니다.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 3, base_delay: Duration::from_millis(500), max_delay: Duration::from_secs(5) }
+    }
+}
+
+impl RetryPolicy {
+    /// `retried`번 재시도한 뒤 다음 재시도 전 대기 시간
+    pub fn delay(&self, retried: u32) -> Duration {
+        self.base_delay.saturating_mul(2u32.saturating_pow(retried)).min(self.max_delay)
+    }
+
+    /// 다시 보내도 되는 오류인지 확인합니다.
+    ///
+    /// 호출 한도 초과는 처리되지 않은 요청이므로 항상 다시 보냅니다. 네트워크 오류는 서버가 처리했는지 알 수 없으므로
+    /// 조회(`idempotent`)만 다시 보내고 주문은 중복 접수를 막기 위해 다시 보내지 않습니다.
+    /// 주문 거부 등 응답 오류는 다시 보내도 결과가 같으므로 재시도하지 않습니다.
+    pub fn should_retry(&self, error: &StockrsError, retried: u32, idempotent: bool) -> bool {
+        retried < self.max_retries && match error {
+            StockrsError::Api(ApiError::RateLimited) => true,
+            StockrsError::Api(ApiError::Network(_)) => idempotent,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limiter_and_retry_policy() {
+        // 초당 20건: 첫 요청은 바로, 이후 50ms 간격
+        let limiter = RateLimiter::new(20, 1);
+        let start = Instant::now();
+        for _ in 0..5 {
+            limiter.acquire();
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(190) && elapsed < Duration::from_secs(1), "{:?}", elapsed);
+
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(0), Duration::from_millis(500));
+        assert_eq!(policy.delay(2), Duration::from_secs(2));
+        assert_eq!(policy.delay(10), Duration::from_secs(5));
+
+        let network: StockrsError = ApiError::Network("timeout".to_string()).into();
+        let rejected: StockrsError = ApiError::Response { code: "APBK0919".to_string(), message: "주문가능금액 초과".to_string() }.into();
+        assert!(policy.should_retry(&ApiError::RateLimited.into(), 0, false));
+        assert!(policy.should_retry(&network, 0, true));
+        assert!(!policy.should_retry(&network, 0, false));
+        assert!(!policy.should_retry(&network, 3, true));
+        assert!(!policy.should_retry(&rejected, 0, true));
+    }
+}
@@ -0,0 +1,442 @@
+use crate::api::rate_limit::{RateLimiter, RetryPolicy};
+use crate::error::{ApiError, StockrsError};
+use crate::types::api::ApiEnv;
+use chrono::{Duration, Local, NaiveDateTime};
+use log::{debug, warn};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const REAL_REST_URL: &str = "https://openapi.koreainvestment.com:9443";
+const PAPER_REST_URL: &str = "https://openapivts.koreainvestment.com:29443";
+const REAL_WS_URL: &str = "ws://ops.koreainvestment.com:21000";
+const PAPER_WS_URL: &str = "ws://ops.koreainvestment.com:31000";
+
+/// 만료까지 이 시간보다 적게 남으면 새로 발급받음
+const REFRESH_MARGIN: Duration = Duration::hours(1);
+/// 웹소켓 접속키 유효 시간 (발급 응답에 만료 시각이 없음)
+const APPROVAL_KEY_LIFETIME: Duration = Duration::hours(24);
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// KIS Open API 접속 정보
+///
+/// `korea-investment-api`의 설정 파일과 같은 키 이름을 사용하므로 같은 TOML 파일을 읽을 수 있습니다.
+#[derive(Clone, Deserialize)]
+pub struct KisConfig {
+    pub app_key: String,
+    pub app_secret: String,
+    /// 종합계좌번호 (계좌번호 앞 8자리)
+    pub cano: String,
+    /// 계좌상품코드 (계좌번호 뒤 2자리)
+    pub acnt_prdt_cd: String,
+    /// REST API 주소 (없으면 환경별 기본 주소)
+    #[serde(default)]
+    pub rest_url: Option<String>,
+    /// 토큰 캐시 파일 경로 (없으면 `data/kis_session_{real|paper}.json`)
+    #[serde(default)]
+    pub token_cache: Option<PathBuf>,
+    /// 실시간(웹소켓) 주소 (없으면 환경별 기본 주소)
+    #[serde(default)]
+    pub ws_url: Option<String>,
+    /// 체결통보 구독에 쓰는 HTS ID
+    #[serde(default)]
+    pub hts_id: Option<String>,
+    /// 초당 요청 수 한도 (없으면 환경별 KIS 한도, `RateLimiter::for_env`)
+    #[serde(default)]
+    pub requests_per_second: Option<u32>,
+}
+
+impl KisConfig {
+    pub fn from_file(path: &Path) -> Result<Self, StockrsError> {
+        let content = fs::read_to_string(path).map_err(|e| StockrsError::Config(e.to_string()))?;
+        toml::from_str(&content).map_err(|e| StockrsError::Config(e.to_string()))
+    }
+}
+
+/// 조회 API 응답
+pub struct KisResponse {
+    pub body: Value,
+    /// 다음 페이지가 있는지 여부 (응답 헤더 `tr_cont`가 `F` 또는 `M`)
+    pub has_next: bool,
+}
+
+/// 발급받은 토큰(또는 접속키)과 만료 시각
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Credential {
+    value: String,
+    /// `YYYY-MM-DD HH:MM:SS` (현지 시각)
+    expires_at: String,
+}
+
+impl Credential {
+    fn new(value: String, expires_at: NaiveDateTime) -> Self {
+        Self { value, expires_at: expires_at.format(DATETIME_FORMAT).to_string() }
+    }
+
+    fn expires_at(&self) -> Result<NaiveDateTime, StockrsError> {
+        Ok(NaiveDateTime::parse_from_str(&self.expires_at, DATETIME_FORMAT)?)
+    }
+}
+
+/// 토큰 캐시 파일 내용
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Credentials {
+    access_token: Option<Credential>,
+    approval_key: Option<Credential>,
+}
+
+/// 환경(실전/모의)별 KIS 접속 세션
+///
+/// 접근토큰과 웹소켓 접속키를 메모리와 디스크에 캐시하고, 만료 `REFRESH_MARGIN` 전부터 새로 발급받습니다.
+/// 토큰 발급은 1분당 1회로 제한되므로 재발급에 실패하면 아직 만료되지 않은 기존 토큰을 계속 사용합니다.
+/// 만료 전이라도 서버가 토큰을 거부하면(`EGW00123`, `EGW00121`) 캐시를 지우고 한 번 재발급받아 요청을 다시 보냅니다.
+/// `install`로 등록한 세션을 `KiDataReader`와 브로커가 `session(env)`로 함께 사용합니다.
+///
+/// 모든 조회·주문 요청은 세션의 `RateLimiter`를 거쳐 환경별 초당 거래건수 한도를 지키며,
+/// 실패한 요청은 `RetryPolicy`에 따라 간격을 늘려 가며 다시 보냅니다.
+pub struct KisSession {
+    env: ApiEnv,
+    config: KisConfig,
+    client: Client,
+    credentials: Mutex<Credentials>,
+    limiter: RateLimiter,
+    retry: RetryPolicy,
+}
+
+impl KisSession {
+    /// 캐시 파일이 있으면 저장된 토큰을 불러와 세션을 만듭니다.
+    pub fn new(env: ApiEnv, config: KisConfig) -> Self {
+        let limiter = match config.requests_per_second {
+            Some(rate) => RateLimiter::new(rate, 1),
+            None => RateLimiter::for_env(env),
+        };
+        let mut session = Self {
+            env,
+            config,
+            client: Client::new(),
+            credentials: Mutex::new(Credentials::default()),
+            limiter,
+            retry: RetryPolicy::default(),
+        };
+        let cached = fs::read_to_string(session.cache_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+        if let Some(cached) = cached {
+            session.credentials = Mutex::new(cached);
+        }
+        session
+    }
+
+    /// 재시도 정책을 지정합니다. (기본값: `RetryPolicy::default()`)
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn get_env(&self) -> ApiEnv { self.env }
+    pub fn get_config(&self) -> &KisConfig { &self.config }
+    pub fn get_client(&self) -> &Client { &self.client }
+
+    pub fn get_rest_url(&self) -> &str {
+        match (&self.config.rest_url, self.env) {
+            (Some(url), _) => url,
+            (None, ApiEnv::Real) => REAL_REST_URL,
+            (None, ApiEnv::Paper) => PAPER_REST_URL,
+        }
+    }
+
+    pub fn get_ws_url(&self) -> &str {
+        match (&self.config.ws_url, self.env) {
+            (Some(url), _) => url,
+            (None, ApiEnv::Real) => REAL_WS_URL,
+            (None, ApiEnv::Paper) => PAPER_WS_URL,
+        }
+    }
+
+    /// 유효한 접근토큰을 반환합니다. 없거나 만료가 가까우면 새로 발급받습니다.
+    pub fn access_token(&self) -> Result<String, StockrsError> {
+        self.credential(|c| &mut c.access_token, || self.issue_access_token())
+    }
+
+    /// 유효한 웹소켓 접속키를 반환합니다. 없거나 만료가 가까우면 새로 발급받습니다.
+    pub fn approval_key(&self) -> Result<String, StockrsError> {
+        self.credential(|c| &mut c.approval_key, || self.issue_approval_key())
+    }
+
+    /// 서버가 거부한 접근토큰을 캐시에서 지웁니다. 그 사이 다른 요청이 새로 발급받은 토큰은 유지합니다.
+    fn discard_access_token(&self, rejected: &str) {
+        let mut credentials = self.credentials.lock().unwrap();
+        if credentials.access_token.as_ref().is_some_and(|c| c.value == rejected) {
+            credentials.access_token = None;
+            self.save(&credentials);
+        }
+    }
+
+    fn credential<S, I>(&self, select: S, issue: I) -> Result<String, StockrsError>
+    where
+        S: Fn(&mut Credentials) -> &mut Option<Credential>,
+        I: Fn() -> Result<Credential, StockrsError>,
+    {
+        let mut credentials = self.credentials.lock().unwrap();
+        let now = Local::now().naive_local();
+        let current = select(&mut credentials).clone();
+        if let Some(current) = &current && current.expires_at()? - REFRESH_MARGIN > now {
+            return Ok(current.value.clone());
+        }
+
+        match issue() {
+            Ok(issued) => {
+                let value = issued.value.clone();
+                *select(&mut credentials) = Some(issued);
+                self.save(&credentials);
+                Ok(value)
+            }
+            Err(e) => match current {
+                Some(current) if current.expires_at()? > now => {
+                    warn!("토큰 재발급 실패, 기존 토큰 사용: {}", e);
+                    Ok(current.value)
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    // 접근토큰발급(P)[인증-001]
+
+    // output
+    //  access_token
+    //  접근토큰	String	Y	350
+    //  expires_in
+    //  접근토큰 유효기간	Number	Y	10	초 단위
+    //  access_token_token_expired
+    //  접근토큰 유효기간	String	Y	50	YYYY-MM-DD HH:MM:SS
+
+    fn issue_access_token(&self) -> Result<Credential, StockrsError> {
+        let body = self.post_auth("/oauth2/tokenP", json!({
+            "grant_type": "client_credentials",
+            "appkey": self.config.app_key,
+            "appsecret": self.config.app_secret,
+        }))?;
+        let token = body["access_token"]
+            .as_str()
+            .ok_or_else(|| ApiError::Auth("응답에 access_token이 없습니다".to_string()))?;
+        let expires_at = match body["access_token_token_expired"].as_str() {
+            Some(expired) => NaiveDateTime::parse_from_str(expired, DATETIME_FORMAT)?,
+            None => Local::now().naive_local() + Duration::seconds(body["expires_in"].as_i64().unwrap_or(86_400)),
+        };
+        Ok(Credential::new(token.to_string(), expires_at))
+    }
+
+    // 실시간 (웹소켓) 접속키 발급[실시간-000]
+
+    // output
+    //  approval_key
+    //  웹소켓 접속키	String	Y	286
+
+    fn issue_approval_key(&self) -> Result<Credential, StockrsError> {
+        let body = self.post_auth("/oauth2/Approval", json!({
+            "grant_type": "client_credentials",
+            "appkey": self.config.app_key,
+            "secretkey": self.config.app_secret,
+        }))?;
+        let key = body["approval_key"]
+            .as_str()
+            .ok_or_else(|| ApiError::Auth("응답에 approval_key가 없습니다".to_string()))?;
+        Ok(Credential::new(key.to_string(), Local::now().naive_local() + APPROVAL_KEY_LIFETIME))
+    }
+
+    /// 인증이 필요한 조회 API를 호출하고 응답 본문과 연속조회 여부(`tr_cont` 응답 헤더)를 반환합니다.
+    ///
+    /// `tr_cont`는 첫 조회면 빈 문자열, 다음 페이지 조회면 `"N"`입니다.
+    pub fn get(&self, path: &str, tr_id: &str, tr_cont: &str, query: &[(&str, String)]) -> Result<KisResponse, StockrsError> {
+        let request = self.client
+            .get(format!("{}{}", self.get_rest_url(), path))
+            .query(query);
+        self.send(request, tr_id, tr_cont, true)
+    }
+
+    /// 인증이 필요한 주문 API를 JSON 본문으로 호출합니다.
+    ///
+    /// 네트워크 오류는 주문이 접수되었는지 알 수 없으므로 다시 보내지 않습니다. (`RetryPolicy::should_retry`)
+    pub fn post(&self, path: &str, tr_id: &str, body: &Value) -> Result<KisResponse, StockrsError> {
+        // content-type은 send에서 지정하므로 본문만 설정
+        let request = self.client
+            .post(format!("{}{}", self.get_rest_url(), path))
+            .body(body.to_string());
+        self.send(request, tr_id, "", false)
+    }
+
+    /// 요청 한도를 지켜 보내고, 재시도할 수 있는 오류면 `RetryPolicy`의 간격만큼 기다려 다시 보냅니다.
+    ///
+    /// 토큰이 거부되면 처리되지 않은 요청이므로 주문도 토큰을 재발급받아 한 번 다시 보냅니다.
+    fn send(&self, request: RequestBuilder, tr_id: &str, tr_cont: &str, idempotent: bool) -> Result<KisResponse, StockrsError> {
+        let mut retried = 0;
+        let mut reissued = false;
+        loop {
+            // 본문이 스트림이 아니므로 항상 복제됨
+            let attempt = request.try_clone().ok_or_else(|| ApiError::Network("요청을 복제할 수 없습니다".to_string()))?;
+            let token = self.access_token()?;
+            self.limiter.acquire();
+            match self.send_once(attempt, &token, tr_id, tr_cont) {
+                Err(StockrsError::Api(ApiError::TokenRejected(message))) if !reissued => {
+                    warn!("[{}] 접근토큰이 거부되어 재발급 후 다시 보냅니다: {}", tr_id, message);
+                    self.discard_access_token(&token);
+                    reissued = true;
+                }
+                Err(e) if self.retry.should_retry(&e, retried, idempotent) => {
+                    let delay = self.retry.delay(retried);
+                    retried += 1;
+                    debug!("[{}] 요청 실패, {:?} 후 재시도 {}/{}: {}", tr_id, delay, retried, self.retry.max_retries, e);
+                    thread::sleep(delay);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn send_once(&self, request: RequestBuilder, token: &str, tr_id: &str, tr_cont: &str) -> Result<KisResponse, StockrsError> {
+        let response = request
+            .header("content-type", "application/json; charset=utf-8")
+            .header("authorization", format!("Bearer {}", token))
+            .header("appkey", &self.config.app_key)
+            .header("appsecret", &self.config.app_secret)
+            .header("tr_id", tr_id)
+            .header("tr_cont", tr_cont)
+            .header("custtype", "P")
+            .send()
+            .map_err(|e| ApiError::Network(e.to_string()))?;
+        let has_next = matches!(
+            response.headers().get("tr_cont").and_then(|v| v.to_str().ok()),
+            Some("F") | Some("M")
+        );
+        let body: Value = response.json().map_err(|e| ApiError::Network(e.to_string()))?;
+        if body["rt_cd"].as_str() != Some("0") {
+            let code = body["msg_cd"].as_str().unwrap_or_default().to_string();
+            let message = body["msg1"].as_str().unwrap_or_default().trim().to_string();
+            return Err(match code.as_str() {
+                // 초당 거래건수 초과
+                "EGW00201" => ApiError::RateLimited,
+                // 기간이 만료된 token / 유효하지 않은 token
+                "EGW00123" | "EGW00121" => ApiError::TokenRejected(message),
+                _ => ApiError::Response { code, message },
+            }.into());
+        }
+        Ok(KisResponse { body, has_next })
+    }
+
+    /// 인증 API를 호출합니다. 발급 횟수 제한(`EGW00133`)은 `ApiError::RateLimited`로 반환합니다.
+    fn post_auth(&self, path: &str, body: Value) -> Result<Value, StockrsError> {
+        let response = self.client
+            .post(format!("{}{}", self.get_rest_url(), path))
+            .json(&body)
+            .send()
+            .map_err(|e| ApiError::Network(e.to_string()))?;
+        let status = response.status();
+        let body: Value = response.json().map_err(|e| ApiError::Network(e.to_string()))?;
+        if !status.is_success() {
+            let code = body["error_code"].as_str().unwrap_or_default();
+            let message = body["error_description"].as_str().unwrap_or_default();
+            return Err(match code {
+                "EGW00133" => ApiError::RateLimited,
+                _ => ApiError::Auth(format!("[{}] {}", code, message)),
+            }.into());
+        }
+        Ok(body)
+    }
+
+    fn cache_path(&self) -> PathBuf {
+        match (&self.config.token_cache, self.env) {
+            (Some(path), _) => path.clone(),
+            (None, ApiEnv::Real) => PathBuf::from("data/kis_session_real.json"),
+            (None, ApiEnv::Paper) => PathBuf::from("data/kis_session_paper.json"),
+        }
+    }
+
+    /// 캐시 파일에 저장합니다. 저장에 실패해도 메모리의 토큰으로 계속 동작합니다.
+    fn save(&self, credentials: &Credentials) {
+        let path = self.cache_path();
+        let result = serde_json::to_string_pretty(credentials)
+            .map_err(|e| e.to_string())
+            .and_then(|content| fs::write(&path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("토큰 캐시 저장 실패 {}: {}", path.display(), e);
+        }
+    }
+}
+
+// ------------------------------------------------
+// KiDataReader / 브로커가 함께 사용하는 환경별 세션
+// ------------------------------------------------
+
+static REAL_SESSION: Mutex<Option<Arc<KisSession>>> = Mutex::new(None);
+static PAPER_SESSION: Mutex<Option<Arc<KisSession>>> = Mutex::new(None);
+
+fn slot(env: ApiEnv) -> &'static Mutex<Option<Arc<KisSession>>> {
+    match env {
+        ApiEnv::Real => &REAL_SESSION,
+        ApiEnv::Paper => &PAPER_SESSION,
+    }
+}
+
+/// 환경별 세션을 등록합니다. 이전에 등록된 세션은 교체됩니다.
+pub fn install(env: ApiEnv, config: KisConfig) -> Arc<KisSession> {
+    let session = Arc::new(KisSession::new(env, config));
+    *slot(env).lock().unwrap() = Some(session.clone());
+    session
+}
+
+/// 등록된 세션을 반환합니다.
+pub fn session(env: ApiEnv) -> Result<Arc<KisSession>, StockrsError> {
+    let session = slot(env).lock().unwrap().clone();
+    Ok(session.ok_or(ApiError::NoSession(env))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(cache: PathBuf) -> KisConfig {
+        KisConfig {
+            app_key: "key".to_string(),
+            app_secret: "secret".to_string(),
+            cano: "12345678".to_string(),
+            acnt_prdt_cd: "01".to_string(),
+            // 연결이 거부되는 주소로 발급 요청은 항상 실패
+            rest_url: Some("http://127.0.0.1:9".to_string()),
+            token_cache: Some(cache),
+            ws_url: None,
+            hts_id: None,
+            requests_per_second: None,
+        }
+    }
+
+    fn write_cache(path: &Path, token: &str, expires_at: NaiveDateTime) {
+        let credentials = Credentials { access_token: Some(Credential::new(token.to_string(), expires_at)), approval_key: None };
+        fs::write(path, serde_json::to_string(&credentials).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_cached_token_lifecycle() {
+        let path = std::env::temp_dir().join(format!("stockrs_session_test_{}.json", std::process::id()));
+        let now = Local::now().naive_local();
+
+        // 만료까지 여유가 있으면 발급 요청 없이 캐시 사용
+        write_cache(&path, "cached", now + Duration::hours(10));
+        assert_eq!(KisSession::new(ApiEnv::Paper, config(path.clone())).access_token().unwrap(), "cached");
+
+        // 만료가 가까워 재발급을 시도하지만 실패하면 기존 토큰 사용
+        write_cache(&path, "expiring", now + Duration::minutes(30));
+        assert_eq!(KisSession::new(ApiEnv::Paper, config(path.clone())).access_token().unwrap(), "expiring");
+
+        // 만료된 토큰은 사용하지 않고 발급 실패를 반환
+        write_cache(&path, "expired", now - Duration::minutes(1));
+        let err = KisSession::new(ApiEnv::Paper, config(path.clone())).access_token().unwrap_err();
+        assert!(err.is_retryable());
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,73 @@
+use chrono::{Local, NaiveDate};
+use clap::Parser;
+use log::{info, warn};
+use std::path::PathBuf;
+use stockrs::api::session::{self, KisConfig};
+use stockrs::downloader::PriceDownloader;
+use stockrs::error::StockrsError;
+use stockrs::price_db::PriceDB;
+use stockrs::types::api::ApiEnv;
+
+/// KIS 당일분봉조회·기간별시세로 백테스트용 시세 DB를 만듭니다.
+///
+/// 중단 후 같은 인자로 다시 실행하면 이미 받은 구간은 건너뛰고 이어받습니다.
+#[derive(Parser)]
+#[command(name = "download_prices", about = "KIS 과거 시세 다운로드")]
+struct Opt {
+    /// KIS 설정 파일 (TOML)
+    #[arg(long)]
+    config: PathBuf,
+    /// 시세를 저장할 DB 파일 (거래 기록 DB와 같은 파일을 사용해도 됨)
+    #[arg(long)]
+    db: PathBuf,
+    /// 모의투자 서버 사용
+    #[arg(long)]
+    paper: bool,
+    /// 일봉 시작일 (YYYYMMDD, 없으면 일봉을 받지 않음)
+    #[arg(long, value_parser = parse_date)]
+    from: Option<NaiveDate>,
+    /// 일봉 종료일 (YYYYMMDD, 기본값: 오늘)
+    #[arg(long, value_parser = parse_date)]
+    to: Option<NaiveDate>,
+    /// 당일 분봉을 받지 않음
+    #[arg(long)]
+    no_minute: bool,
+    /// 종목코드 목록
+    #[arg(required = true)]
+    stockcodes: Vec<String>,
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(s, "%Y%m%d").map_err(|e| format!("날짜 형식 오류 (YYYYMMDD): {}", e))
+}
+
+fn main() -> Result<(), StockrsError> {
+    env_logger::init();
+    let opt = Opt::parse();
+    let env = if opt.paper { ApiEnv::Paper } else { ApiEnv::Real };
+    session::install(env, KisConfig::from_file(&opt.config)?);
+    let mut downloader = PriceDownloader::new(PriceDB::new(opt.db.clone())?, env);
+    let to = opt.to.unwrap_or_else(|| Local::now().date_naive());
+
+    // 한 종목이 실패해도 나머지는 받고, 실패한 종목은 다시 실행하면 이어받음
+    let mut failed = Vec::new();
+    for stockcode in &opt.stockcodes {
+        let mut result = Ok(0);
+        if !opt.no_minute {
+            result = downloader.download_minute_bars(stockcode);
+        }
+        if let (Ok(_), Some(from)) = (&result, opt.from) {
+            result = downloader.download_daily_bars(stockcode, from, to);
+        }
+        if let Err(e) = result {
+            warn!("[{}] 시세 다운로드 실패: {}", stockcode, e);
+            failed.push(stockcode.clone());
+        }
+    }
+
+    info!("시세 다운로드 완료: {}종목 중 {}종목 실패", opt.stockcodes.len(), failed.len());
+    match failed.is_empty() {
+        true => Ok(()),
+        false => Err(StockrsError::Config(format!("다운로드 실패 종목: {}", failed.join(", ")))),
+    }
+}
@@ -0,0 +1,31 @@
+use clap::Parser;
+use log::info;
+use std::path::PathBuf;
+use stockrs::api::mock_kis::{MockFixtures, MockKisServer};
+use stockrs::error::StockrsError;
+
+/// 네트워크 없이 KIS REST API를 흉내내는 모의 서버
+///
+/// 설정 파일의 `rest_url`을 이 서버 주소로 지정하면 실계좌 없이 모의투자 흐름을 실행할 수 있습니다.
+#[derive(Parser)]
+#[command(name = "mock_kis", about = "KIS REST API 모의 서버")]
+struct Opt {
+    /// 대기할 주소
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: String,
+    /// 계좌·시세 초기 상태 JSON 파일 (`MockFixtures`)
+    fixtures: Option<PathBuf>,
+}
+
+fn main() -> Result<(), StockrsError> {
+    env_logger::init();
+    let opt = Opt::parse();
+    let fixtures = match &opt.fixtures {
+        Some(path) => MockFixtures::from_file(path)?,
+        None => MockFixtures::default(),
+    };
+    let server = MockKisServer::bind(&opt.addr, fixtures)?;
+    info!("KIS 모의 서버 실행 중: {}", server.get_url());
+    server.wait();
+    Ok(())
+}
@@ -1,69 +1,369 @@
-use crate::api::koreainvestapi::{execute_order, check_fill, cancel_order};
-use crate::api::db_api::{execute_order_from_db, check_fill_from_db, cancel_order_from_db};
+use crate::error::{ApiError, StockrsError};
+use crate::api::koreainvestapi::{execute_order, check_fill, cancel_order, amend_order, get_buyable_cash, get_domestic006_result, get_price_info};
+use crate::api::db_api::DbApi;
 use crate::db_manager::DBManager;
-use crate::types::broker::{Broker, BrokerType, Order};
+use crate::fee::FeeModel;
+use crate::krx::{price_limits, validate_price, TickPolicy};
+use crate::order_tracker::OrderTracker;
+use crate::stream::fill::FillStream;
+use crate::types::broker::{Broker, BrokerType, Order, OrderSide, OrderStatus, ValidationError};
 use crate::types::api::ApiEnv;
-use chrono::Duration;
-use std::error::Error;
-use std::thread;
-
-fn execute_common(order: &Order, db: &DBManager, env: ApiEnv) -> Result<(), Box<dyn Error>> {
-    let order_id = execute_order(order, env)?;
-    let filled = check_fill(&order_id, env)?;
-    if filled {
-        db.save_trading(order.to_trading())?;
-    }
-    thread::sleep(Duration::minutes(5).to_std().unwrap());
-    cancel_order(&order_id, env)?;
+use crate::types::market::Market;
+use chrono::{Duration, Local};
+use log::debug;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// 주문별 `timeout`이 없을 때 미체결 주문을 취소하기까지의 기본 시간
+pub const DEFAULT_ORDER_TIMEOUT: Duration = Duration::minutes(5);
+
+/// 수량과 KRX 호가단위·가격제한폭, 주문가능금액·매도가능수량을 검증하고, 제출할 주문을 반환합니다.
+///
+/// `TickPolicy::Round`이면 호가단위를 벗어난 가격을 조정한 주문을 반환합니다.
+/// 체결분의 세금 계산에 쓰도록 종목의 상장 시장도 함께 반환합니다.
+fn validate_common(order: &Order, env: ApiEnv, tick_policy: TickPolicy, fee_model: &FeeModel) -> Result<(Order, Market), StockrsError> {
+    if order.quantity == 0 {
+        return Err(ValidationError::InvalidQuantity.into());
+    }
+    let info = get_price_info(&order.stockcode, env)?;
+    let date = order.date.date();
+    let order = match order.order_type.has_limit_price() {
+        true => Order { price: validate_price(order.price, order.side, &info, date, tick_policy)?, ..order.clone() },
+        false => order.clone(),
+    };
+    // 가격을 지정하지 않는 주문은 상한가로 체결된다고 보고 필요 금액을 계산
+    let worst_price = match order.order_type.has_limit_price() {
+        true => order.price,
+        false => price_limits(info.get_prev_close(), info.get_market(), date).1,
+    };
+    check_funds(&order, worst_price, info.get_market(), env, fee_model)?;
+    Ok((order, info.get_market()))
+}
+
+/// 매수는 주문가능금액, 매도는 매도가능수량이 충분한지 계좌를 조회해 확인합니다.
+///
+/// 매수 필요 금액에는 `FeeModel`로 계산한 수수료를 포함합니다.
+fn check_funds(order: &Order, price: f64, market: Market, env: ApiEnv, fee_model: &FeeModel) -> Result<(), StockrsError> {
+    match order.side {
+        OrderSide::Buy => {
+            let fee = fee_model.fee(order.side, market, price, order.quantity, order.date.date());
+            let required = price * order.quantity as f64 + fee;
+            let available = get_buyable_cash(&order.stockcode, price, env)?;
+            if required > available {
+                return Err(ValidationError::InsufficientCash { required, available }.into());
+            }
+        }
+        OrderSide::Sell => {
+            let available = get_domestic006_result(env)?.get_ord_psbl_qty(&order.stockcode);
+            if order.quantity > available {
+                return Err(ValidationError::InsufficientHoldings {
+                    stockcode: order.stockcode.clone(),
+                    requested: order.quantity,
+                    available,
+                }.into());
+            }
+        }
+    }
     Ok(())
 }
 
-pub struct RealBroker;
-impl Broker for RealBroker {
-    fn validate(&self, _order: &Order) -> Result<(), Box<dyn Error>> {
-        // TODO: add real validation logic
-        Ok(())
+fn execute_common(order: &Order, db: &DBManager, env: ApiEnv, tick_policy: TickPolicy, fee_model: &FeeModel, tracker: &OrderTracker, fill_stream: Option<&FillStream>) -> Result<String, StockrsError> {
+    let (order, market) = validate_common(order, env, tick_policy, fee_model)?;
+    let order_id = execute_order(&order, env)?;
+    tracker.register(order_id.clone(), &order, market);
+    // 즉시 체결된 주문은 바로 기록
+    poll_common(db, env, tracker, fill_stream)?;
+    Ok(order_id)
+}
+
+/// 정정 후 주문을 만듭니다. 새 잔량이 0이거나 현재 잔량보다 크면 거부합니다.
+fn amended_order(order_id: &str, price: Option<f64>, quantity: Option<u32>, tracker: &OrderTracker) -> Result<(Order, u32), StockrsError> {
+    let (order, filled) = tracker
+        .active_order(order_id)
+        .ok_or_else(|| ValidationError::NotAmendable(order_id.to_string()))?;
+    let remaining = order.quantity - filled;
+    let quantity = quantity.unwrap_or(remaining);
+    if quantity == 0 || quantity > remaining {
+        return Err(ValidationError::InvalidQuantity.into());
     }
+    Ok((Order { price: price.unwrap_or(order.price), quantity, ..order }, remaining))
+}
 
-    fn execute(&self, order: &Order, db: &DBManager) -> Result<(), Box<dyn Error>> {
-        self.validate(order)?;
-        execute_common(order, db, ApiEnv::Real)
+/// 정정 직전까지의 체결은 호출 전에 `poll_common`으로 반영되어 있어야 합니다.
+fn amend_common(order_id: &str, price: Option<f64>, quantity: Option<u32>, env: ApiEnv, tick_policy: TickPolicy, tracker: &OrderTracker) -> Result<String, StockrsError> {
+    let (amended, remaining) = amended_order(order_id, price, quantity, tracker)?;
+    let amended = match amended.order_type.has_limit_price() {
+        true => {
+            let info = get_price_info(&amended.stockcode, env)?;
+            let price = validate_price(amended.price, amended.side, &info, amended.date.date(), tick_policy)?;
+            Order { price, ..amended }
+        }
+        false => amended,
+    };
+
+    let new_order_id = amend_order(order_id, &amended, env)?;
+    // 정정 주문은 이미 접수되었으므로 취소 결과와 관계없이 추적
+    let shrunk = amended.quantity < remaining;
+    tracker.replace(order_id, new_order_id.clone(), amended)?;
+    // 잔량을 줄인 경우 원주문에 남은 수량은 취소
+    if shrunk && let Err(e) = cancel_order(order_id, env) {
+        // 남은 잔량은 계속 추적하여 체결을 기록하고 시한에 다시 취소
+        tracker.reactivate(order_id);
+        return Err(ApiError::RemainderNotCancelled {
+            order_id: order_id.to_string(),
+            new_order_id,
+            message: e.to_string(),
+        }.into());
     }
+    Ok(new_order_id)
 }
 
-pub struct PaperBroker;
-impl Broker for PaperBroker {
-    fn validate(&self, order: &Order) -> Result<(), Box<dyn Error>> {
-        RealBroker.validate(order)
+/// 체결통보로 받은 체결을 먼저 기록한 뒤, 체결 조회로 빠진 체결과 주문 상태를 확인합니다.
+fn poll_common(db: &DBManager, env: ApiEnv, tracker: &OrderTracker, fill_stream: Option<&FillStream>) -> Result<(), StockrsError> {
+    let notices = fill_stream.map(FillStream::drain).unwrap_or_default();
+    if !notices.is_empty() {
+        db.invalidate_account();
+    }
+    for notice in notices {
+        if let Some(fill) = notice.to_fill() && !tracker.apply_notice(db, &fill)? {
+            debug!("추적하지 않는 주문의 체결통보: {}", fill.order_id);
+        }
     }
+    tracker.poll(
+        db,
+        Local::now().naive_local(),
+        |order_id| check_fill(order_id, env),
+        |order_id| cancel_order(order_id, env),
+    )
+}
 
-    fn execute(&self, order: &Order, db: &DBManager) -> Result<(), Box<dyn Error>> {
-        self.validate(order)?;
-        execute_common(order, db, ApiEnv::Paper)
+/// `KisBroker`의 접속 환경
+pub trait KisEnv {
+    const ENV: ApiEnv;
+    /// 환경별 기본 수수료율을 사용하는 거래비용 모델
+    fn fee_model() -> FeeModel;
+}
+
+/// 실전투자 환경
+pub struct RealEnv;
+
+/// 모의투자 환경
+pub struct PaperEnv;
+
+impl KisEnv for RealEnv {
+    const ENV: ApiEnv = ApiEnv::Real;
+    fn fee_model() -> FeeModel { FeeModel::for_broker(&BrokerType::REAL) }
+}
+
+impl KisEnv for PaperEnv {
+    const ENV: ApiEnv = ApiEnv::Paper;
+    fn fee_model() -> FeeModel { FeeModel::for_broker(&BrokerType::PAPER) }
+}
+
+/// KIS Open API로 주문하는 실전/모의투자 브로커
+///
+/// 두 환경은 접속 세션(`session(env)`)과 기본 수수료율만 다르므로 환경(`KisEnv`)을 타입 인자로 받습니다.
+pub struct KisBroker<E: KisEnv> {
+    tracker: OrderTracker,
+    tick_policy: TickPolicy,
+    fee_model: FeeModel,
+    fill_stream: Option<FillStream>,
+    env: PhantomData<E>,
+}
+
+/// 실전투자 브로커
+pub type RealBroker = KisBroker<RealEnv>;
+
+/// 모의투자 브로커
+pub type PaperBroker = KisBroker<PaperEnv>;
+
+impl<E: KisEnv> KisBroker<E> {
+    pub fn new() -> Self {
+        Self::with_order_timeout(DEFAULT_ORDER_TIMEOUT)
+    }
+
+    /// 미체결 주문의 기본 취소 시간을 지정하여 생성합니다.
+    pub fn with_order_timeout(timeout: Duration) -> Self {
+        let fee_model = E::fee_model();
+        Self { tracker: OrderTracker::new(timeout, fee_model), tick_policy: TickPolicy::Reject, fee_model, fill_stream: None, env: PhantomData }
+    }
+
+    /// 호가단위를 벗어난 가격의 처리 방식을 지정합니다. (기본값: 거부)
+    pub fn with_tick_policy(mut self, tick_policy: TickPolicy) -> Self {
+        self.tick_policy = tick_policy;
+        self
+    }
+
+    /// 체결통보 구독을 연결합니다. 통보받은 체결은 체결 조회보다 먼저 기록됩니다.
+    pub fn with_fill_stream(mut self, fill_stream: FillStream) -> Self {
+        self.fill_stream = Some(fill_stream);
+        self
+    }
+
+    pub fn get_env(&self) -> ApiEnv { E::ENV }
+}
+
+impl<E: KisEnv> Default for KisBroker<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: KisEnv> Broker for KisBroker<E> {
+    fn validate(&self, order: &Order) -> Result<(), StockrsError> {
+        validate_common(order, E::ENV, self.tick_policy, &self.fee_model).map(|_| ())
+    }
+
+    fn execute(&self, order: &Order, db: &DBManager) -> Result<String, StockrsError> {
+        execute_common(order, db, E::ENV, self.tick_policy, &self.fee_model, &self.tracker, self.fill_stream.as_ref())
+    }
+
+    fn poll(&self, db: &DBManager) -> Result<(), StockrsError> {
+        poll_common(db, E::ENV, &self.tracker, self.fill_stream.as_ref())
+    }
+
+    fn amend(&self, order_id: &str, price: Option<f64>, quantity: Option<u32>, db: &DBManager) -> Result<String, StockrsError> {
+        // 정정 직전까지의 체결을 먼저 반영
+        poll_common(db, E::ENV, &self.tracker, self.fill_stream.as_ref())?;
+        amend_common(order_id, price, quantity, E::ENV, self.tick_policy, &self.tracker)
+    }
+
+    fn expire_all(&self, _db: &DBManager) -> Result<(), StockrsError> {
+        self.tracker.expire_all(|order_id| cancel_order(order_id, E::ENV))
+    }
+
+    fn status(&self, order_id: &str) -> Option<OrderStatus> {
+        self.tracker.status(order_id)
+    }
+}
+
+/// 과거 분봉 데이터(`DbApi`)로 체결을 시뮬레이션하는 브로커
+///
+/// 시한 판단에는 백테스트의 가상 시각을 사용하고, 수수료는 `DbApi`의 거래비용 모델을 따릅니다.
+pub struct DbBroker {
+    api: Arc<DbApi>,
+    tracker: OrderTracker,
+}
+
+impl DbBroker {
+    pub fn new(api: Arc<DbApi>) -> Self {
+        Self::with_order_timeout(api, DEFAULT_ORDER_TIMEOUT)
+    }
+
+    /// 미체결 주문의 기본 취소 시간을 지정하여 생성합니다.
+    pub fn with_order_timeout(api: Arc<DbApi>, timeout: Duration) -> Self {
+        let tracker = OrderTracker::new(timeout, api.get_fee_model());
+        Self { api, tracker }
     }
 }
 
-pub struct DbBroker;
 impl Broker for DbBroker {
-    fn validate(&self, _order: &Order) -> Result<(), Box<dyn Error>> { Ok(()) }
+    fn validate(&self, _order: &Order) -> Result<(), StockrsError> { Ok(()) }
 
-    fn execute(&self, order: &Order, db: &DBManager) -> Result<(), Box<dyn Error>> {
+    fn execute(&self, order: &Order, db: &DBManager) -> Result<String, StockrsError> {
         self.validate(order)?;
-        let order_id = execute_order_from_db(order)?;
-        let filled = check_fill_from_db(&order_id)?;
-        if filled {
-            db.save_trading(order.to_trading())?;
-        }
-        thread::sleep(Duration::minutes(5).to_std().unwrap());
-        cancel_order_from_db(&order_id)?;
-        Ok(())
+        let order_id = self.api.execute_order(order)?;
+        self.tracker.register(order_id.clone(), order, self.api.get_market(&order.stockcode)?);
+        self.poll(db)?;
+        Ok(order_id)
+    }
+
+    fn poll(&self, db: &DBManager) -> Result<(), StockrsError> {
+        self.tracker.poll(db, self.api.now(), |id| self.api.check_fill(id), |id| self.api.cancel_order(id))
+    }
+
+    fn amend(&self, order_id: &str, price: Option<f64>, quantity: Option<u32>, db: &DBManager) -> Result<String, StockrsError> {
+        self.poll(db)?;
+        let (amended, _) = amended_order(order_id, price, quantity, &self.tracker)?;
+        let new_order_id = self.api.amend_order(order_id, &amended)?;
+        self.tracker.replace(order_id, new_order_id.clone(), amended)?;
+        Ok(new_order_id)
+    }
+
+    fn expire_all(&self, _db: &DBManager) -> Result<(), StockrsError> {
+        self.tracker.expire_all(|id| self.api.cancel_order(id))
+    }
+
+    fn status(&self, order_id: &str) -> Option<OrderStatus> {
+        self.tracker.status(order_id)
     }
 }
 
 pub fn make_broker(kind: BrokerType) -> Box<dyn Broker> {
     match kind {
-        BrokerType::REAL => Box::new(RealBroker),
-        BrokerType::PAPER => Box::new(PaperBroker),
-        BrokerType::DB => Box::new(DbBroker),
+        BrokerType::REAL => Box::new(RealBroker::new()),
+        BrokerType::PAPER => Box::new(PaperBroker::new()),
+        BrokerType::DB(api) => Box::new(DbBroker::new(api)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_kis::{MockFixtures, MockHolding, MockKisServer, MockStock};
+    use crate::types::broker::{test_order, OrderType};
+    use crate::types::data_reader::DataReaderType;
+    use std::path::PathBuf;
+
+    fn server(auto_fill: bool) -> MockKisServer {
+        MockKisServer::start(MockFixtures {
+            cash: 1_000_000.0,
+            holdings: vec![MockHolding { stockcode: "005930".to_string(), quantity: 5, avg_price: 68_000.0 }],
+            stocks: vec![MockStock { stockcode: "005930".to_string(), name: String::new(), market: Market::KOSPI, prev_close: 70_000.0, price: None }],
+            auto_fill,
+            ..MockFixtures::default()
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_validate_rejects_before_submit() {
+        let server = server(true);
+        let _session = server.install(ApiEnv::Real);
+        let broker = RealBroker::new();
+
+        assert!(broker.validate(&test_order(Local::now().naive_local(), OrderSide::Buy, OrderType::Limit, 10, 70_000.0)).is_ok());
+        let rejected = |order: Order| match broker.validate(&order) {
+            Err(StockrsError::Validation(e)) => e,
+            other => panic!("검증 오류가 아님: {:?}", other.err()),
+        };
+        assert!(matches!(rejected(test_order(Local::now().naive_local(), OrderSide::Buy, OrderType::Limit, 0, 70_000.0)), ValidationError::InvalidQuantity));
+        assert!(matches!(rejected(test_order(Local::now().naive_local(), OrderSide::Buy, OrderType::Limit, 1, 70_050.0)), ValidationError::InvalidTick { .. }));
+        assert!(matches!(rejected(test_order(Local::now().naive_local(), OrderSide::Buy, OrderType::Limit, 1, 95_000.0)), ValidationError::OutOfPriceBand { .. }));
+        assert!(matches!(rejected(test_order(Local::now().naive_local(), OrderSide::Buy, OrderType::Limit, 15, 70_000.0)), ValidationError::InsufficientCash { .. }));
+        assert!(matches!(rejected(test_order(Local::now().naive_local(), OrderSide::Sell, OrderType::Limit, 6, 70_000.0)), ValidationError::InsufficientHoldings { available: 5, .. }));
+
+        // 거부된 주문은 제출되지 않음
+        let db = DBManager::new(PathBuf::from(":memory:"), DataReaderType::REAL).unwrap();
+        assert!(broker.execute(&test_order(Local::now().naive_local(), OrderSide::Buy, OrderType::Limit, 15, 70_000.0), &db).is_err());
+        assert_eq!(server.get_cash(), 1_000_000.0);
+    }
+
+    #[test]
+    fn test_execute_poll_amend_expire() {
+        let server = server(false);
+        let _session = server.install(ApiEnv::Real);
+        let db = DBManager::new(PathBuf::from(":memory:"), DataReaderType::REAL).unwrap();
+        let broker = RealBroker::new();
+
+        let order_id = broker.execute(&test_order(Local::now().naive_local(), OrderSide::Buy, OrderType::Limit, 10, 70_000.0), &db).unwrap();
+        assert_eq!(broker.status(&order_id), Some(OrderStatus::Submitted));
+        broker.poll(&db).unwrap();
+        assert!(db.holdings().unwrap().is_empty());
+
+        // 조회한 부분 체결만 거래로 기록
+        server.fill(&order_id, 4, 70_000.0).unwrap();
+        broker.poll(&db).unwrap();
+        assert_eq!(broker.status(&order_id), Some(OrderStatus::PartiallyFilled));
+        let holdings = db.holdings().unwrap();
+        assert_eq!((holdings[0].get_stockcode(), holdings[0].get_quantity()), ("005930", 4));
+
+        // 잔량 6주를 3주로 줄이면 새 주문을 추적하고 원주문 잔량은 취소
+        let amended = broker.amend(&order_id, Some(69_900.0), Some(3), &db).unwrap();
+        assert_eq!(broker.status(&amended), Some(OrderStatus::Submitted));
+        assert!(broker.status(&order_id).unwrap().is_terminal());
+
+        broker.expire_all(&db).unwrap();
+        assert_eq!(broker.status(&amended), Some(OrderStatus::Expired));
+        assert_eq!(server.get_quantity("005930"), 9);
     }
 }
@@ -1,47 +1,303 @@
-use crate::api::koreainvestapi::get_domestic006_result;
+use crate::error::{ApiError, StockrsError};
+use crate::api::koreainvestapi::{get_current_price, get_domestic006_result};
+use crate::api::db_api::DbApi;
 use crate::api::result::Domestic006Result;
+use crate::price_db::PriceDB;
+use crate::time::{Clock, WallClock};
 use crate::types::api::ApiEnv;
 use crate::types::data_reader::{DataReader, DataReaderType};
-use crate::types::trading::AssetInfo;
+use crate::types::trading::{AssetInfo, Holding};
+use chrono::{NaiveDateTime, Timelike};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+/// 잔고조회 결과를 다시 쓰는 시간
+///
+/// 한 번의 업데이트 안에서 여러 체결 기록과 overview 갱신이 같은 잔고를 보도록 하되,
+/// 다음 업데이트(1분 뒤)에는 새로 조회할 만큼 짧게 둡니다.
+const SNAPSHOT_TTL: Duration = Duration::from_secs(5);
 
+/// KIS 잔고조회로 자산, 평균매입가, 보유 종목과 예수금을 읽는 리더
+///
+/// 현재가는 매번 주식현재가 시세로 조회합니다. 잔고조회 결과를 `SNAPSHOT_TTL` 동안 재사용하여 같은 업데이트 안의 조회가 한 번의 잔고조회로 처리되며,
+/// 체결이 보고되면(`invalidate`) 다음 조회에서 새로 읽습니다.
 struct KiDataReader {
     env: ApiEnv,
+    /// (조회 시각, 잔고조회 결과)
+    snapshot: Mutex<Option<(Instant, Arc<Domestic006Result>)>>,
 }
 
 impl KiDataReader {
     fn new(env: ApiEnv) -> Self {
-        Self { env }
+        Self { env, snapshot: Mutex::new(None) }
+    }
+
+    fn balance(&self) -> Result<Arc<Domestic006Result>, StockrsError> {
+        // 조회하는 동안 잠가 두어 동시에 들어온 조회도 한 번만 요청
+        let mut snapshot = self.snapshot.lock().unwrap();
+        if let Some((fetched_at, result)) = snapshot.as_ref() && fetched_at.elapsed() < SNAPSHOT_TTL {
+            return Ok(result.clone());
+        }
+        let result = Arc::new(get_domestic006_result(self.env)?);
+        *snapshot = Some((Instant::now(), result.clone()));
+        Ok(result)
     }
 }
 
 impl DataReader for KiDataReader {
-    fn get_asset_info(&self) -> Result<AssetInfo, Box<dyn std::error::Error>> {
-        let result: Domestic006Result = get_domestic006_result(self.env)?;
-        Ok(result.into())
+    fn get_asset_info(&self) -> Result<AssetInfo, StockrsError> {
+        Ok(self.balance()?.as_ref().into())
+    }
+
+    fn get_avg_price(&self, stockcode: String) -> Result<f64, StockrsError> {
+        self.balance()?.get_pchs_avg_pric(stockcode)
+    }
+
+    fn get_holdings(&self) -> Result<Vec<Holding>, StockrsError> {
+        let mut holdings: Vec<Holding> = self.balance()?.get_holdings()
+            .iter()
+            .filter(|item| item.get_hldg_qty() > 0)
+            .map(|item| {
+                Holding::new(item.get_pdno().to_string(), item.get_hldg_qty(), item.get_pchs_avg_pric())
+                    .with_orderable_quantity(item.get_ord_psbl_qty())
+            })
+            .collect();
+        holdings.sort_by(|a, b| a.get_stockcode().cmp(b.get_stockcode()));
+        Ok(holdings)
+    }
+
+    fn get_orderable_cash(&self) -> Result<f64, StockrsError> {
+        Ok(self.balance()?.get_summary().get_prvs_rcdl_excc_amt())
+    }
+
+    fn get_current_price(&self, stockcode: &str) -> Result<f64, StockrsError> {
+        get_current_price(stockcode, self.env)
+    }
+
+    fn invalidate(&self) {
+        *self.snapshot.lock().unwrap() = None;
+    }
+}
+
+/// 백테스트용 가상 계좌(`DbApi`)에서 자산, 평균매입가, 보유 종목, 예수금과 현재가를 읽는 리더
+pub struct SimDataReader {
+    api: Arc<DbApi>,
+}
+
+impl SimDataReader {
+    pub fn new(api: Arc<DbApi>) -> Self {
+        Self { api }
+    }
+}
+
+impl DataReader for SimDataReader {
+    fn get_asset_info(&self) -> Result<AssetInfo, StockrsError> {
+        self.api.get_asset_info()
+    }
+    fn get_avg_price(&self, stockcode: String) -> Result<f64, StockrsError> {
+        self.api.get_avg_price(&stockcode)
+    }
+    fn get_holdings(&self) -> Result<Vec<Holding>, StockrsError> {
+        self.api.get_holdings()
+    }
+    fn get_orderable_cash(&self) -> Result<f64, StockrsError> {
+        Ok(self.api.get_cash())
+    }
+    fn get_current_price(&self, stockcode: &str) -> Result<f64, StockrsError> {
+        self.api.get_current_price(stockcode)
+    }
+}
+
+/// 종목별 (보유수량, 평균매입가)
+type LedgerBook = HashMap<String, (u32, f64)>;
+
+/// stockrs DB의 거래 기록(`trading`)과 시세(`PriceDB`)로 자산과 평균매입가를 계산하는 리더
+///
+/// - 평균매입가: 매수·매도 내역을 순서대로 반영한 종목별 평균매입가 (수수료 제외, 전량 매도 후에도 유지)
+/// - 자산: 초기 예수금에 체결금액과 수수료를 반영한 예수금 + 보유 종목을 현재 시각 전에 완성된 마지막 분봉의 종가로 평가한 금액
+/// - 보유 종목: 거래 기록에는 미체결 주문이 없으므로 주문가능수량은 보유수량과 같음
+///
+/// 백테스트 가상 계좌(`DbApi`)와 같은 방식으로 계산하므로, 같은 초기 예수금과 시세 DB를 주면
+/// 백테스트가 끝난 뒤의 오프라인 분석에서도 같은 `AssetInfo`를 얻습니다.
+pub struct DbDataReader<C: Clock = WallClock> {
+    ledger: Connection,
+    prices: PriceDB,
+    initial_cash: f64,
+    clock: C,
+}
+
+impl DbDataReader {
+    /// `path`의 거래 기록과 같은 파일의 시세 테이블을 읽습니다.
+    pub fn new(path: PathBuf, initial_cash: f64) -> Result<Self, StockrsError> {
+        Ok(Self {
+            ledger: Connection::open(&path)?,
+            prices: PriceDB::new(path)?,
+            initial_cash,
+            clock: WallClock,
+        })
+    }
+}
+
+impl<C: Clock> DbDataReader<C> {
+    /// 보유 종목 평가에 사용할 시세 DB를 지정합니다. (기본값: 거래 기록과 같은 파일)
+    pub fn with_prices(mut self, prices: PriceDB) -> Self {
+        self.prices = prices;
+        self
+    }
+
+    /// 평가 시각을 읽을 시계를 지정합니다. (기본값: `WallClock`, 백테스트는 `SimulatedClock`)
+    pub fn with_clock<D: Clock>(self, clock: D) -> DbDataReader<D> {
+        DbDataReader { ledger: self.ledger, prices: self.prices, initial_cash: self.initial_cash, clock }
     }
 
-    fn get_avg_price(&self, stockcode: String) -> Result<f64, Box<dyn std::error::Error>> {
-        let result: Domestic006Result = get_domestic006_result(self.env)?;
-        let avg = result.get_pchs_avg_pric(stockcode)?;
-        Ok(avg)
+    /// 현재 시각이 속한 분봉의 시작 시각
+    fn current_minute(&self) -> NaiveDateTime {
+        let now = self.clock.now().naive_local();
+        now.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(now)
+    }
+
+    /// 거래 내역을 순서대로 반영한 예수금과 종목별 보유 현황
+    fn replay(&self) -> Result<(f64, LedgerBook), StockrsError> {
+        let mut stmt = self.ledger.prepare(
+            "SELECT stockcode, buy_or_sell, quantity, price, COALESCE(fee, 0) FROM trading ORDER BY id",
+        )?;
+        let rows = stmt.query_map((), |row| {
+            let buy_or_sell: String = row.get(1)?;
+            Ok((row.get::<_, String>(0)?, buy_or_sell == "buy", row.get::<_, u32>(2)?, row.get::<_, f64>(3)?, row.get::<_, f64>(4)?))
+        })?;
+
+        let mut cash = self.initial_cash;
+        let mut book = LedgerBook::new();
+        for row in rows {
+            let (stockcode, is_buy, quantity, price, fee) = row?;
+            let amount = price * quantity as f64;
+            let (held, avg_price) = book.entry(stockcode).or_insert((0, 0.0));
+            if is_buy {
+                cash -= amount + fee;
+                let total = *held + quantity;
+                *avg_price = (*avg_price * *held as f64 + amount) / total as f64;
+                *held = total;
+            } else {
+                cash += amount - fee;
+                *held = held.saturating_sub(quantity);
+            }
+        }
+        Ok((cash, book))
     }
 }
 
-pub struct DbDataReader;
-impl DataReader for DbDataReader {
-    fn get_asset_info(&self) -> Result<AssetInfo, Box<dyn std::error::Error>> {
-        todo!("get asset info");
+impl<C: Clock> DataReader for DbDataReader<C> {
+    fn get_asset_info(&self) -> Result<AssetInfo, StockrsError> {
+        let minute = self.current_minute();
+        let (mut asset, book) = self.replay()?;
+        for (stockcode, (quantity, avg_price)) in book.iter().filter(|(_, (quantity, _))| *quantity > 0) {
+            let price = self.prices.get_last_close(stockcode, minute)?.unwrap_or(*avg_price);
+            asset += price * *quantity as f64;
+        }
+        Ok(AssetInfo::new(self.clock.now().naive_local(), asset))
+    }
+
+    fn get_avg_price(&self, stockcode: String) -> Result<f64, StockrsError> {
+        let (_, book) = self.replay()?;
+        let (_, avg_price) = book.get(&stockcode).ok_or(ApiError::StockNotFound(stockcode))?;
+        Ok(*avg_price)
+    }
+
+    fn get_holdings(&self) -> Result<Vec<Holding>, StockrsError> {
+        let (_, book) = self.replay()?;
+        let mut holdings: Vec<Holding> = book.into_iter()
+            .filter(|(_, (quantity, _))| *quantity > 0)
+            .map(|(stockcode, (quantity, avg_price))| Holding::new(stockcode, quantity, avg_price))
+            .collect();
+        holdings.sort_by(|a, b| a.get_stockcode().cmp(b.get_stockcode()));
+        Ok(holdings)
     }
-    fn get_avg_price(&self, stockcode: String) -> Result<f64, Box<dyn std::error::Error>> {
-        todo!("get avg price");
+
+    fn get_orderable_cash(&self) -> Result<f64, StockrsError> {
+        Ok(self.replay()?.0)
+    }
+
+    fn get_current_price(&self, stockcode: &str) -> Result<f64, StockrsError> {
+        let price = self.prices.get_last_close(stockcode, self.current_minute())?;
+        Ok(price.ok_or_else(|| ApiError::NoPrice(stockcode.to_string()))?)
     }
 }
 
 pub fn make_data_reader(kind: DataReaderType) -> Box<dyn DataReader> {
     match kind {
         DataReaderType::REAL => Box::new(KiDataReader::new(ApiEnv::Real)),
-        DataReaderType::DB => Box::new(DbDataReader),
+        DataReaderType::DB(api) => Box::new(SimDataReader::new(api)),
         DataReaderType::PAPER => Box::new(KiDataReader::new(ApiEnv::Paper)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_kis::{MockFixtures, MockHolding, MockKisServer};
+    use crate::db_manager::DBManager;
+    use crate::time::SimulatedClock;
+    use crate::types::price::Bar;
+    use chrono::{Local, NaiveDate, TimeZone};
+
+    #[test]
+    fn test_balance_snapshot_reuse_and_invalidate() {
+        let server = MockKisServer::start(MockFixtures {
+            holdings: vec![MockHolding { stockcode: "005930".to_string(), quantity: 10, avg_price: 70000.0 }],
+            ..MockFixtures::default()
+        }).unwrap();
+        let _session = server.install(ApiEnv::Paper);
+        let balance_count = || server.get_request_count("/uapi/domestic-stock/v1/trading/inquire-balance");
+        let reader = KiDataReader::new(ApiEnv::Paper);
+
+        // TTL 안의 조회는 한 번의 잔고조회 결과를 함께 사용
+        reader.get_asset_info().unwrap();
+        assert_eq!(reader.get_avg_price("005930".to_string()).unwrap(), 70000.0);
+        assert_eq!(reader.get_holdings().unwrap().len(), 1);
+        assert_eq!(balance_count(), 1);
+
+        reader.invalidate();
+        reader.get_orderable_cash().unwrap();
+        assert_eq!(balance_count(), 2);
+    }
+
+    #[test]
+    fn test_ledger_asset_and_avg_price() {
+        let path = std::env::temp_dir().join(format!("stockrs_ledger_reader_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let clock = SimulatedClock::new(Local.with_ymd_and_hms(2025, 7, 16, 9, 1, 30).unwrap());
+        let api = Arc::new(DbApi::new(PriceDB::new(PathBuf::from(":memory:")).unwrap(), clock.clone(), 0.0));
+        let _db = DBManager::new(path.clone(), DataReaderType::DB(api)).unwrap();
+        let ledger = Connection::open(&path).unwrap();
+        for (side, quantity, price, fee) in [("buy", 10, 70000.0, 100.0), ("buy", 10, 72000.0, 100.0), ("sell", 5, 75000.0, 500.0)] {
+            ledger.execute(
+                "INSERT INTO trading (date, time, stockcode, buy_or_sell, quantity, price, fee) VALUES ('2025-07-16', '09:01:00', '005930', ?, ?, ?, ?)",
+                (side, quantity, price, fee),
+            ).unwrap();
+        }
+        let prices = PriceDB::new(path.clone()).unwrap();
+        let bar_time = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 2, 0).unwrap();
+        prices.upsert_minute_bar("005930", &Bar::new(bar_time, 73000.0, 73000.0, 73000.0, 73000.0, 1)).unwrap();
+
+        let reader = DbDataReader::new(path.clone(), 2_000_000.0).unwrap().with_clock(clock.clone());
+        assert_eq!(reader.get_avg_price("005930".to_string()).unwrap(), 71000.0);
+        assert!(reader.get_avg_price("000660".to_string()).is_err());
+        let holdings = reader.get_holdings().unwrap();
+        assert_eq!(holdings.len(), 1);
+        assert_eq!((holdings[0].get_quantity(), holdings[0].get_orderable_quantity()), (15, 15));
+        assert_eq!(reader.get_orderable_cash().unwrap(), 954_300.0);
+        assert!(reader.get_current_price("005930").is_err());
+
+        // 예수금 2,000,000 - 1,420,200 + 374,500 = 954,300, 시세가 없으면 평균매입가로 평가
+        assert_eq!(reader.get_asset_info().unwrap().get_asset(), 954_300.0 + 15.0 * 71000.0);
+        // 현재 시각 전에 완성된 마지막 분봉의 종가로 평가
+        clock.set(Local.with_ymd_and_hms(2025, 7, 16, 9, 3, 0).unwrap());
+        assert_eq!(reader.get_asset_info().unwrap().get_asset(), 954_300.0 + 15.0 * 73000.0);
+        assert_eq!(reader.get_current_price("005930").unwrap(), 73000.0);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
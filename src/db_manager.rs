@@ -1,18 +1,49 @@
-use rusqlite::{Connection, Result};
-use crate::types::trading::Trading;
+use chrono::NaiveDateTime;
+use rusqlite::{Connection, OptionalExtension};
+use crate::error::StockrsError;
+use crate::types::trading::{AssetInfo, Holding, Position, Trading};
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
 use crate::types::data_reader::{DataReader, DataReaderType};
 use crate::data_reader::make_data_reader;
+use log::warn;
 
+/// 데이터 리더 조회가 실패했을 때의 처리 방식
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReaderFallback {
+    /// 오류를 그대로 반환
+    Fail,
+    /// 경고만 남기고 overview 갱신을 건너뜀
+    Skip,
+    /// 재시도 가능한 오류(`StockrsError::is_retryable`)면 `delay` 간격으로 최대 `attempts`번 다시 조회하고,
+    /// 그래도 실패하면 오류를 반환
+    Retry { attempts: u32, delay: Duration },
+    /// 마지막으로 성공한 조회 값을 사용 (성공한 적이 없으면 오류 반환)
+    LastKnown,
+}
+
+/// 거래 내역(`trading`), 종목별 포지션(`positions`)과 일별 자산 요약(`overview`)을 기록합니다.
+///
+/// 포지션은 체결을 기록할 때 같은 트랜잭션에서 갱신하므로 거래 내역과 항상 일치합니다.
+///
+/// 거래 내역의 평균매입가와 손익은 포지션으로 계산하므로 체결 기록은 데이터 리더 조회에 의존하지 않습니다.
+/// overview에 쓰는 자산은 데이터 리더로 조회하며, 조회 실패는 `ReaderFallback`에 따라 처리합니다.
 pub struct DBManager {
     conn: Connection,
     data_reader: Box<dyn DataReader>,
+    fallback: ReaderFallback,
+    last_asset_info: Mutex<Option<AssetInfo>>,
 }
 
 impl DBManager {
-    pub fn new(path: PathBuf, data_reader_type: DataReaderType) -> Result<Self> {
+    pub fn new(path: PathBuf, data_reader_type: DataReaderType) -> Result<Self, StockrsError> {
+        Self::with_data_reader(path, make_data_reader(data_reader_type))
+    }
+
+    pub fn with_data_reader(path: PathBuf, data_reader: Box<dyn DataReader>) -> Result<Self, StockrsError> {
         let conn = Connection::open(path)?;
-        let data_reader = make_data_reader(data_reader_type);
 
         // Create trading table
         conn.execute(
@@ -28,10 +59,37 @@ impl DBManager {
                 strategy TEXT,
                 avg_price REAL,
                 profit REAL,
-                roi REAL
+                roi REAL,
+                order_id TEXT
+            )",
+            (),
+        )?;
+
+        // Add order_id column to trading tables created before fills were linked to orders
+        let has_order_id = conn
+            .prepare("SELECT 1 FROM pragma_table_info('trading') WHERE name = 'order_id'")?
+            .exists(())?;
+        if !has_order_id {
+            conn.execute("ALTER TABLE trading ADD COLUMN order_id TEXT", ())?;
+        }
+
+        // Create positions table, filling it from the trading history if it did not exist
+        let has_positions = conn
+            .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'positions'")?
+            .exists(())?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS positions (
+                stockcode TEXT PRIMARY KEY,
+                quantity INTEGER,
+                avg_price REAL,
+                realized_pnl REAL,
+                first_entry TEXT
             )",
             (),
         )?;
+        if !has_positions {
+            rebuild_positions(&conn)?;
+        }
 
         // Create overview table
         conn.execute(
@@ -50,29 +108,102 @@ impl DBManager {
             (),
         )?;
 
-        Ok(Self { conn, data_reader })
+        Ok(Self {
+            conn,
+            data_reader,
+            fallback: ReaderFallback::Fail,
+            last_asset_info: Mutex::new(None),
+        })
+    }
+
+    /// 데이터 리더 조회 실패 시 처리 방식을 지정합니다. (기본값: `ReaderFallback::Fail`)
+    pub fn with_fallback(mut self, fallback: ReaderFallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// `ReaderFallback::Retry`이면 재시도 가능한 오류에 한해 다시 조회합니다.
+    fn read_with_retry<T>(&self, read: impl Fn() -> Result<T, StockrsError>) -> Result<T, StockrsError> {
+        let mut retried = 0;
+        loop {
+            match read() {
+                Err(e) => match self.fallback {
+                    ReaderFallback::Retry { attempts, delay } if e.is_retryable() && retried < attempts => {
+                        retried += 1;
+                        warn!("데이터 조회 실패, 재시도 {}/{}: {}", retried, attempts, e);
+                        thread::sleep(delay);
+                    }
+                    _ => return Err(e),
+                },
+                result => return result,
+            }
+        }
+    }
+
+    /// 조회 실패를 처리 방식에 따라 오류, 건너뜀(`None`) 또는 마지막 값으로 바꿉니다.
+    fn fallback<T>(&self, what: &str, error: StockrsError, last: Option<T>) -> Result<Option<T>, StockrsError> {
+        match (self.fallback, last) {
+            (ReaderFallback::Skip, _) => {
+                warn!("{} 조회 실패, 건너뜀: {}", what, error);
+                Ok(None)
+            }
+            (ReaderFallback::LastKnown, Some(last)) => {
+                warn!("{} 조회 실패, 마지막 값 사용: {}", what, error);
+                Ok(Some(last))
+            }
+            _ => Err(error),
+        }
+    }
+
+    fn asset_info(&self) -> Result<Option<AssetInfo>, StockrsError> {
+        match self.read_with_retry(|| self.data_reader.get_asset_info()) {
+            Ok(info) => {
+                *self.last_asset_info.lock().unwrap() = Some(info);
+                Ok(Some(info))
+            }
+            Err(e) => self.fallback("자산", e, *self.last_asset_info.lock().unwrap()),
+        }
+    }
+
+    /// 계좌·시세 조회에 사용하는 데이터 리더
+    pub fn reader(&self) -> &dyn DataReader {
+        self.data_reader.as_ref()
+    }
+
+    /// 계좌 상태가 바뀌었음을 데이터 리더에 알립니다. 이후 조회는 캐시 대신 새로 읽습니다.
+    pub fn invalidate_account(&self) {
+        self.data_reader.invalidate();
     }
 
     // Save trading data to database
-    pub fn save_trading(&self, trading: Trading) -> Result<()> {
-        let avg_price = self.data_reader.get_avg_price(trading.get_stockcode().to_string()).unwrap();
+    pub fn save_trading(&self, trading: Trading) -> Result<(), StockrsError> {
+        // Update the position first and record the trade with its average price
+        let tx = self.conn.unchecked_transaction()?;
+        let avg_price = apply_fill(
+            &tx,
+            trading.get_stockcode(),
+            trading.get_buy_or_sell(),
+            trading.get_quantity(),
+            trading.get_price(),
+            trading.get_fee(),
+            &trading.get_date().to_string(),
+        )?;
         let trading_result = trading.to_trading_result(avg_price);
-        
-        // Insert trading data
-        self.conn.execute(
+        tx.execute(
             "INSERT INTO trading (
                 date, time, stockcode, buy_or_sell, quantity, 
-                price, fee, strategy, avg_price, profit, roi
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                price, fee, strategy, avg_price, profit, roi, order_id
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             trading_result.to_db_tuple(),
         )?;
+        tx.commit()?;
 
         Ok(())
     }
 
     // Initialize today's overview data
-    pub fn insert_overview(&self) -> Result<()> {
-        let result = self.data_reader.get_asset_info().unwrap();
+    pub fn insert_overview(&self) -> Result<(), StockrsError> {
+        let Some(result) = self.asset_info()? else { return Ok(()) };
         let date = result.get_date();
         let asset = result.get_asset();
 
@@ -86,10 +217,11 @@ impl DBManager {
     }
 
     // Update today's overview data
-    pub fn update_overview(&self) -> Result<()> {
-        let result = self.data_reader.get_asset_info().unwrap();
+    pub fn update_overview(&self) -> Result<(), StockrsError> {
+        let Some(result) = self.asset_info()? else { return Ok(()) };
         let date = result.get_date();
         let asset = result.get_asset();
+        self.ensure_overview(&result)?;
 
         // Get today's high and low values
         let (high, low) = self.conn.query_row(
@@ -115,10 +247,11 @@ impl DBManager {
     }
 
     // Finalize today's overview data
-    pub fn finish_overview(&self) -> Result<()> {
-        let result = self.data_reader.get_asset_info().unwrap();
+    pub fn finish_overview(&self) -> Result<(), StockrsError> {
+        let Some(result) = self.asset_info()? else { return Ok(()) };
         let date = result.get_date();
         let asset = result.get_asset();
+        self.ensure_overview(&result)?;
 
         let open: f64 = self.conn.query_row(
             "SELECT open FROM overview WHERE date = ?",
@@ -155,4 +288,257 @@ impl DBManager {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    // Create today's overview row if it was skipped at market open
+    fn ensure_overview(&self, result: &AssetInfo) -> Result<(), StockrsError> {
+        let asset = result.get_asset();
+        self.conn.execute(
+            "INSERT OR IGNORE INTO overview (date, open, high, low) VALUES (?, ?, ?, ?)",
+            (result.get_date().date().to_string(), asset, asset, asset),
+        )?;
+        Ok(())
+    }
+
+    /// 보유 종목 (`positions` 테이블에서 보유수량이 있는 종목, 최초 진입 순)
+    pub fn holdings(&self) -> Result<Vec<Holding>, StockrsError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT stockcode, quantity, avg_price FROM positions WHERE quantity > 0 ORDER BY first_entry, stockcode",
+        )?;
+        let rows = stmt.query_map((), |row| Ok(Holding::new(row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// 보유수량이 있는 포지션 (최초 진입 순)
+    pub fn positions(&self) -> Result<Vec<Position>, StockrsError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT stockcode, quantity, avg_price, realized_pnl, first_entry FROM positions
+             WHERE quantity > 0 ORDER BY first_entry, stockcode",
+        )?;
+        let rows = stmt.query_map((), read_position)?;
+        rows.map(|row| to_position(row?)).collect()
+    }
+
+    /// 종목의 포지션. 전량 매도한 종목도 실현손익과 함께 반환하며, 거래한 적이 없으면 `None`입니다.
+    pub fn position(&self, stockcode: &str) -> Result<Option<Position>, StockrsError> {
+        self.conn
+            .query_row(
+                "SELECT stockcode, quantity, avg_price, realized_pnl, first_entry FROM positions WHERE stockcode = ?",
+                (stockcode,),
+                read_position,
+            )
+            .optional()?
+            .map(to_position)
+            .transpose()
+    }
+}
+
+/// `positions` 테이블 한 행 (stockcode, quantity, avg_price, realized_pnl, first_entry)
+type PositionRow = (String, u32, f64, f64, Option<String>);
+
+fn read_position(row: &rusqlite::Row) -> rusqlite::Result<PositionRow> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+}
+
+fn to_position((stockcode, quantity, avg_price, realized_pnl, first_entry): PositionRow) -> Result<Position, StockrsError> {
+    let first_entry = first_entry
+        .map(|time| NaiveDateTime::parse_from_str(&time, "%Y-%m-%d %H:%M:%S%.f"))
+        .transpose()?;
+    Ok(Position::new(stockcode, quantity, avg_price, realized_pnl, first_entry))
+}
+
+/// 체결 한 건을 `positions` 테이블에 반영합니다.
+///
+/// 매수는 평균매입가(수수료 제외)를 갱신하고, 매도는 (매도가 - 평균매입가) * 수량을 실현손익에 더합니다.
+/// 수수료와 세금은 매수·매도 모두 실현손익에서 뺍니다. 전량 매도하면 수량과 최초 진입 시각을 비우고 실현손익은 남깁니다.
+///
+/// 거래 내역에 기록할 평균매입가를 반환합니다. 매수는 반영 후, 매도는 반영 전 평균매입가이며
+/// 보유하지 않은 종목의 매도는 체결가입니다.
+fn apply_fill(conn: &Connection, stockcode: &str, is_buy: bool, quantity: u32, price: f64, fee: f64, time: &str) -> Result<f64, StockrsError> {
+    let current: Option<(u32, f64, f64, Option<String>)> = conn
+        .query_row(
+            "SELECT quantity, avg_price, realized_pnl, first_entry FROM positions WHERE stockcode = ?",
+            (stockcode,),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()?;
+    let (held, avg_price, realized_pnl, first_entry) = current.unwrap_or((0, 0.0, 0.0, None));
+    // 매도는 반영 전 평균매입가로 기록 (보유하지 않은 종목이면 체결가)
+    let sold_avg_price = if held > 0 { avg_price } else { price };
+
+    let (held, avg_price, realized_pnl, first_entry) = match is_buy {
+        true if quantity > 0 => {
+            let total = held + quantity;
+            let avg_price = (avg_price * held as f64 + price * quantity as f64) / total as f64;
+            let first_entry = if held == 0 { Some(time.to_string()) } else { first_entry };
+            (total, avg_price, realized_pnl - fee, first_entry)
+        }
+        true => (held, avg_price, realized_pnl - fee, first_entry),
+        false => {
+            // 기록 밖에서 보유한 수량은 평균매입가를 알 수 없으므로 실현손익에 넣지 않음
+            let sold = quantity.min(held);
+            let realized_pnl = realized_pnl + (price - avg_price) * sold as f64 - fee;
+            match held - sold {
+                0 => (0, 0.0, realized_pnl, None),
+                remaining => (remaining, avg_price, realized_pnl, first_entry),
+            }
+        }
+    };
+
+    conn.execute(
+        "INSERT INTO positions (stockcode, quantity, avg_price, realized_pnl, first_entry)
+         VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT(stockcode) DO UPDATE SET
+            quantity = excluded.quantity,
+            avg_price = excluded.avg_price,
+            realized_pnl = excluded.realized_pnl,
+            first_entry = excluded.first_entry",
+        (stockcode, held, avg_price, realized_pnl, first_entry),
+    )?;
+    Ok(if is_buy { avg_price } else { sold_avg_price })
+}
+
+/// 거래 내역을 처음부터 반영하여 `positions` 테이블을 다시 만듭니다.
+fn rebuild_positions(conn: &Connection) -> Result<(), StockrsError> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM positions", ())?;
+    {
+        let mut stmt = tx.prepare(
+            "SELECT stockcode, buy_or_sell, quantity, price, COALESCE(fee, 0), date || ' ' || time FROM trading ORDER BY id",
+        )?;
+        let rows = stmt.query_map((), |row| {
+            let buy_or_sell: String = row.get(1)?;
+            Ok((row.get::<_, String>(0)?, buy_or_sell == "buy", row.get::<_, u32>(2)?, row.get::<_, f64>(3)?, row.get::<_, f64>(4)?, row.get::<_, String>(5)?))
+        })?;
+        for row in rows {
+            let (stockcode, is_buy, quantity, price, fee, time) = row?;
+            apply_fill(&tx, &stockcode, is_buy, quantity, price, fee, &time)?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ApiError;
+    use chrono::NaiveDate;
+    use std::cell::Cell;
+
+    /// 처음 `ok_calls`번만 자산 조회에 성공하고, 평균매입가 조회는 항상 실패하는 리더
+    struct FlakyReader {
+        ok_calls: Cell<u32>,
+    }
+
+    impl DataReader for FlakyReader {
+        fn get_asset_info(&self) -> Result<AssetInfo, StockrsError> {
+            if self.ok_calls.get() == 0 {
+                return Err(ApiError::Network("timeout".to_string()).into());
+            }
+            self.ok_calls.set(self.ok_calls.get() - 1);
+            Ok(AssetInfo::new(NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 0, 0).unwrap(), 1_000_000.0))
+        }
+        fn get_avg_price(&self, _stockcode: String) -> Result<f64, StockrsError> {
+            Err(ApiError::Network("timeout".to_string()).into())
+        }
+        fn get_holdings(&self) -> Result<Vec<Holding>, StockrsError> {
+            Err(ApiError::Network("timeout".to_string()).into())
+        }
+        fn get_orderable_cash(&self) -> Result<f64, StockrsError> {
+            Err(ApiError::Network("timeout".to_string()).into())
+        }
+        fn get_current_price(&self, _stockcode: &str) -> Result<f64, StockrsError> {
+            Err(ApiError::Network("timeout".to_string()).into())
+        }
+    }
+
+    fn flaky_db(ok_calls: u32, fallback: ReaderFallback) -> DBManager {
+        let reader = Box::new(FlakyReader { ok_calls: Cell::new(ok_calls) });
+        DBManager::with_data_reader(PathBuf::from(":memory:"), reader).unwrap().with_fallback(fallback)
+    }
+
+    fn insert_trade(db: &DBManager, stockcode: &str, side: &str, quantity: u32, price: f64) {
+        db.conn.execute(
+            "INSERT INTO trading (date, time, stockcode, buy_or_sell, quantity, price) VALUES ('2025-07-16', '09:01:00', ?, ?, ?, ?)",
+            (stockcode, side, quantity, price),
+        ).unwrap();
+    }
+
+    #[test]
+    fn test_holdings_from_positions() {
+        let db = DBManager::with_data_reader(PathBuf::from(":memory:"), Box::new(FlakyReader { ok_calls: Cell::new(0) })).unwrap();
+        insert_trade(&db, "005930", "buy", 10, 70000.0);
+        insert_trade(&db, "005930", "buy", 10, 72000.0);
+        insert_trade(&db, "005930", "sell", 5, 75000.0);
+        insert_trade(&db, "000660", "buy", 3, 200000.0);
+        insert_trade(&db, "000660", "sell", 3, 210000.0);
+        // 거래 내역만 직접 넣었으므로 포지션이 아직 비어 있음
+        assert!(db.holdings().unwrap().is_empty());
+        rebuild_positions(&db.conn).unwrap();
+
+        let holdings = db.holdings().unwrap();
+        assert_eq!(holdings.len(), 1);
+        assert_eq!(holdings[0].get_stockcode(), "005930");
+        assert_eq!(holdings[0].get_quantity(), 15);
+        assert_eq!(holdings[0].get_avg_price(), 71000.0);
+    }
+
+    #[test]
+    fn test_positions_follow_fills() {
+        let path = std::env::temp_dir().join(format!("stockrs_positions_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let at = |m| NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, m, 0).unwrap();
+        let trade = |m, buy, quantity, price, fee| Trading::new(at(m), "005930".to_string(), buy, quantity, price, fee, "test".to_string());
+
+        let reader = Box::new(FlakyReader { ok_calls: Cell::new(0) });
+        let db = DBManager::with_data_reader(path.clone(), reader).unwrap().with_fallback(ReaderFallback::Skip);
+        db.save_trading(trade(1, true, 10, 70000.0, 100.0)).unwrap();
+        db.save_trading(trade(2, true, 10, 72000.0, 100.0)).unwrap();
+        db.save_trading(trade(3, false, 5, 75000.0, 500.0)).unwrap();
+
+        // 실현손익 = 4,000 * 5 - 수수료 700
+        let expected = Position::new("005930".to_string(), 15, 71000.0, 19_300.0, Some(at(1)));
+        assert_eq!(db.positions().unwrap(), vec![expected.clone()]);
+
+        // 전량 매도하면 현재 포지션에서 빠지고 실현손익만 남음
+        db.save_trading(trade(4, false, 15, 70000.0, 0.0)).unwrap();
+        assert!(db.positions().unwrap().is_empty());
+        let closed = db.position("005930").unwrap().unwrap();
+        assert_eq!((closed.get_quantity(), closed.get_realized_pnl(), closed.get_first_entry()), (0, 4_300.0, None));
+        assert!(db.position("000660").unwrap().is_none());
+
+        // 포지션 테이블이 없던 DB는 거래 내역으로 채움
+        db.save_trading(trade(5, true, 3, 69000.0, 0.0)).unwrap();
+        db.conn.execute("DROP TABLE positions", ()).unwrap();
+        drop(db);
+        let db = DBManager::with_data_reader(path.clone(), Box::new(FlakyReader { ok_calls: Cell::new(0) })).unwrap();
+        let reopened = db.positions().unwrap();
+        assert_eq!(reopened, vec![Position::new("005930".to_string(), 3, 69000.0, 4_300.0, Some(at(5)))]);
+
+        drop(db);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_reader_fallback() {
+        let db = flaky_db(1, ReaderFallback::Fail);
+        db.insert_overview().unwrap();
+        assert!(db.update_overview().is_err());
+
+        let db = flaky_db(1, ReaderFallback::LastKnown);
+        db.insert_overview().unwrap();
+        db.update_overview().unwrap();
+        db.finish_overview().unwrap();
+        let close: f64 = db.conn.query_row("SELECT close FROM overview WHERE date = '2025-07-16'", (), |row| row.get(0)).unwrap();
+        assert_eq!(close, 1_000_000.0);
+
+        // 리더 조회가 실패해도 체결은 포지션의 평균매입가로 기록
+        let db = flaky_db(0, ReaderFallback::Fail);
+        assert!(db.insert_overview().is_err());
+        let time = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 5, 0).unwrap();
+        db.save_trading(Trading::new(time, "005930".to_string(), true, 10, 70000.0, 0.0, "test".to_string())).unwrap();
+        db.save_trading(Trading::new(time, "005930".to_string(), false, 5, 75000.0, 0.0, "test".to_string())).unwrap();
+        let profit: f64 = db.conn.query_row("SELECT profit FROM trading WHERE buy_or_sell = 'sell'", (), |row| row.get(0)).unwrap();
+        assert_eq!(profit, 25_000.0);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::api::koreainvestapi::{get_daily_bars, get_minute_bars};
+use crate::error::StockrsError;
+use crate::price_db::{DownloadProgress, PriceDB, PriceKind};
+use crate::types::api::ApiEnv;
+use crate::types::price::Bar;
+use chrono::{Duration as ChronoDuration, NaiveDate, NaiveTime};
+use log::info;
+
+fn market_open() -> NaiveTime { NaiveTime::from_hms_opt(9, 0, 0).unwrap() }
+fn market_close() -> NaiveTime { NaiveTime::from_hms_opt(15, 30, 0).unwrap() }
+
+/// 과거 시세를 한 페이지씩 돌려주는 조회처
+pub trait PriceSource {
+    /// 가장 최근 영업일의 `until` 이전 분봉 한 페이지 (시간 오름차순)
+    fn minute_bars(&self, stockcode: &str, until: NaiveTime) -> Result<Vec<Bar>, StockrsError>;
+    /// `from`~`to` 중 최근 일봉 한 페이지 (날짜 오름차순)
+    fn daily_bars(&self, stockcode: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<Bar>, StockrsError>;
+}
+
+/// `install`로 등록된 해당 환경의 KIS 세션으로 조회
+impl PriceSource for ApiEnv {
+    fn minute_bars(&self, stockcode: &str, until: NaiveTime) -> Result<Vec<Bar>, StockrsError> {
+        get_minute_bars(stockcode, until, *self)
+    }
+
+    fn daily_bars(&self, stockcode: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<Bar>, StockrsError> {
+        get_daily_bars(stockcode, from, to, *self)
+    }
+}
+
+/// KIS 당일분봉조회·기간별시세를 최근부터 과거로 넘기며 받아 `PriceDB`에 저장합니다.
+///
+/// 페이지마다 봉과 진행 위치(`DownloadProgress`)를 함께 저장하므로, 중단 후 다시 실행하면
+/// 이미 받은 구간은 건너뛰고 그 이전부터 이어받습니다.
+/// KIS 호출 한도와 재시도는 세션(`KisSession`)이 처리합니다.
+pub struct PriceDownloader<S: PriceSource = ApiEnv> {
+    db: PriceDB,
+    source: S,
+}
+
+impl PriceDownloader<ApiEnv> {
+    pub fn new(db: PriceDB, env: ApiEnv) -> Self {
+        Self::with_source(db, env)
+    }
+}
+
+impl<S: PriceSource> PriceDownloader<S> {
+    pub fn with_source(db: PriceDB, source: S) -> Self {
+        Self { db, source }
+    }
+
+    pub fn get_db(&self) -> &PriceDB { &self.db }
+
+    /// 가장 최근 영업일의 분봉을 장 마감부터 장 시작까지 받고 저장한 분봉 수를 반환합니다.
+    pub fn download_minute_bars(&mut self, stockcode: &str) -> Result<usize, StockrsError> {
+        let previous = self.db.get_download_progress(stockcode, PriceKind::Minute)?;
+        let mut until = market_close();
+        let mut range: Option<DownloadProgress> = None;
+        let mut saved = 0;
+        loop {
+            let bars = self.source.minute_bars(stockcode, until)?;
+            let (first, last) = match (bars.first(), bars.last()) {
+                (Some(first), Some(last)) => (first.get_time(), last.get_time()),
+                _ => {
+                    // 더 이전 분봉이 없음
+                    if let Some(range) = &mut range {
+                        range.done = true;
+                        self.db.save_download_page(stockcode, PriceKind::Minute, &[], range)?;
+                    }
+                    break;
+                }
+            };
+            let last = range.map_or(last, |range| range.last);
+            let mut current = DownloadProgress { first, last, done: first.time() <= market_open() };
+            // 이전 실행에서 받은 같은 날 구간에 닿으면 합쳐서 그 아래부터 이어받음
+            if let Some(prev) = previous.filter(|prev| prev.first.date() == first.date() && first <= prev.last) {
+                current = DownloadProgress {
+                    first: first.min(prev.first),
+                    last: last.max(prev.last),
+                    done: current.done || prev.done,
+                };
+            }
+            self.db.save_download_page(stockcode, PriceKind::Minute, &bars, &current)?;
+            saved += bars.len();
+            range = Some(current);
+            if current.done {
+                break;
+            }
+            until = (current.first - ChronoDuration::seconds(1)).time();
+        }
+        info!("[{}] 분봉 {}개 저장", stockcode, saved);
+        Ok(saved)
+    }
+
+    /// `from`~`to`의 일봉을 최근부터 받고 저장한 일봉 수를 반환합니다.
+    pub fn download_daily_bars(&mut self, stockcode: &str, from: NaiveDate, to: NaiveDate) -> Result<usize, StockrsError> {
+        let previous = self.db.get_download_progress(stockcode, PriceKind::Daily)?;
+        let mut end = to;
+        let mut range: Option<DownloadProgress> = None;
+        let mut saved = 0;
+        while end >= from {
+            // 이전 실행에서 받은 구간은 건너뜀
+            if let Some(prev) = previous.filter(|prev| prev.first.date() <= end && end <= prev.last.date()) {
+                let current = DownloadProgress {
+                    first: prev.first,
+                    last: range.map_or(prev.last, |range| range.last.max(prev.last)),
+                    done: false,
+                };
+                self.db.save_download_page(stockcode, PriceKind::Daily, &[], &current)?;
+                range = Some(current);
+                end = prev.first.date() - ChronoDuration::days(1);
+                continue;
+            }
+
+            let bars = self.source.daily_bars(stockcode, from, end)?;
+            // 빈 페이지면 `from`까지 더 받을 일봉이 없음
+            let first = bars.first().map_or(from, |bar| bar.get_time().date());
+            let current = DownloadProgress {
+                first: first.and_time(NaiveTime::MIN),
+                last: range.map_or(to.and_time(NaiveTime::MIN), |range| range.last),
+                done: false,
+            };
+            self.db.save_download_page(stockcode, PriceKind::Daily, &bars, &current)?;
+            saved += bars.len();
+            range = Some(current);
+            if bars.is_empty() {
+                break;
+            }
+            end = first - ChronoDuration::days(1);
+        }
+        info!("[{}] 일봉 {}개 저장", stockcode, saved);
+        Ok(saved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ApiError;
+    use std::cell::Cell;
+    use std::path::PathBuf;
+
+    /// 페이지 크기가 작은 메모리 시세. `fail_after`번 요청 뒤에는 오류를 돌려줍니다.
+    struct MemorySource {
+        minute: Vec<Bar>,
+        daily: Vec<Bar>,
+        requests: Cell<usize>,
+        fail_after: Cell<Option<usize>>,
+    }
+
+    impl MemorySource {
+        fn page(&self, mut bars: Vec<Bar>) -> Result<Vec<Bar>, StockrsError> {
+            self.requests.set(self.requests.get() + 1);
+            if self.fail_after.get().is_some_and(|limit| self.requests.get() > limit) {
+                return Err(ApiError::Auth("중단".to_string()).into());
+            }
+            bars.sort_by_key(|bar| std::cmp::Reverse(bar.get_time()));
+            bars.truncate(3);
+            bars.reverse();
+            Ok(bars)
+        }
+    }
+
+    impl PriceSource for &MemorySource {
+        fn minute_bars(&self, _stockcode: &str, until: NaiveTime) -> Result<Vec<Bar>, StockrsError> {
+            self.page(self.minute.iter().filter(|bar| bar.get_time().time() <= until).copied().collect())
+        }
+
+        fn daily_bars(&self, _stockcode: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<Bar>, StockrsError> {
+            self.page(self.daily.iter().filter(|bar| (from..=to).contains(&bar.get_time().date())).copied().collect())
+        }
+    }
+
+    #[test]
+    fn test_resume_after_interruption() {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap();
+        let bar = |time| Bar::new(time, 100.0, 100.0, 100.0, 100.0, 1);
+        let source = MemorySource {
+            // 09:00 ~ 09:09 분봉 10개, 7월 1일 ~ 10일 일봉 10개
+            minute: (0..10).map(|m| bar(date.and_hms_opt(9, m, 0).unwrap())).collect(),
+            daily: (1..=10).map(|d| bar(NaiveDate::from_ymd_opt(2025, 7, d).unwrap().and_time(NaiveTime::MIN))).collect(),
+            requests: Cell::new(0),
+            fail_after: Cell::new(Some(2)),
+        };
+        let db = PriceDB::new(PathBuf::from(":memory:")).unwrap();
+        let mut downloader = PriceDownloader::with_source(db, &source);
+
+        // 두 페이지(6개)를 받은 뒤 중단
+        assert!(downloader.download_minute_bars("005930").is_err());
+        assert_eq!(downloader.get_db().count_bars("005930", PriceKind::Minute).unwrap(), 6);
+
+        // 다시 실행하면 첫 페이지로 이전 구간을 확인한 뒤 나머지만 받음
+        source.fail_after.set(None);
+        source.requests.set(0);
+        assert_eq!(downloader.download_minute_bars("005930").unwrap(), 3 + 4);
+        assert_eq!(source.requests.get(), 3);
+        assert_eq!(downloader.get_db().count_bars("005930", PriceKind::Minute).unwrap(), 10);
+        let progress = downloader.get_db().get_download_progress("005930", PriceKind::Minute).unwrap().unwrap();
+        assert!(progress.done);
+
+        // 일봉: 7월 5일까지 받은 뒤 기간을 넓히면 받은 구간은 요청하지 않음
+        let (july, to) = (NaiveDate::from_ymd_opt(2025, 7, 5).unwrap(), NaiveDate::from_ymd_opt(2025, 7, 10).unwrap());
+        assert_eq!(downloader.download_daily_bars("005930", july, to).unwrap(), 6);
+        source.requests.set(0);
+        assert_eq!(downloader.download_daily_bars("005930", NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(), to).unwrap(), 4);
+        // 4개(3+1) 후 빈 페이지
+        assert_eq!(source.requests.get(), 3);
+        assert_eq!(downloader.get_db().count_bars("005930", PriceKind::Daily).unwrap(), 10);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::types::api::ApiEnv;
+use crate::types::broker::ValidationError;
+use std::num::{ParseFloatError, ParseIntError};
+use thiserror::Error;
+
+/// 라이브러리 전체에서 사용하는 오류
+///
+/// 호출자는 변형으로 오류 종류를 구분하여 재시도 여부 등을 결정할 수 있습니다. (`is_retryable` 참고)
+#[derive(Debug, Error)]
+pub enum StockrsError {
+    /// 증권사 API 또는 백테스트 API 호출 실패
+    #[error(transparent)]
+    Api(#[from] ApiError),
+    /// 주문 제출 전 검증 실패
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    /// 거래 기록/시세 DB 오류
+    #[error("DB 오류: {0}")]
+    Db(#[from] rusqlite::Error),
+    /// 응답 값이나 저장된 값의 변환 실패
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    /// 시각 계산 또는 변환 실패
+    #[error("시간 오류: {0}")]
+    Time(String),
+    /// 매매 모델에서 발생한 오류
+    #[error("모델 오류: {0}")]
+    Model(String),
+    /// 설정 파일을 읽거나 해석하지 못함
+    #[error("설정 오류: {0}")]
+    Config(String),
+}
+
+impl StockrsError {
+    /// 잠시 후 같은 요청을 다시 보내면 성공할 수 있는 오류인지 여부
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, StockrsError::Api(ApiError::RateLimited | ApiError::Network(_)))
+    }
+}
+
+/// API 호출 실패 사유
+#[derive(Debug, Error)]
+pub enum ApiError {
+    #[error("API 호출 한도를 초과했습니다")]
+    RateLimited,
+    #[error("네트워크 오류: {0}")]
+    Network(String),
+    #[error("인증 오류: {0}")]
+    Auth(String),
+    /// 만료되었거나 유효하지 않은 접근토큰 (`EGW00123`, `EGW00121`)
+    #[error("접근토큰 거부: {0}")]
+    TokenRejected(String),
+    /// 응답의 오류 코드(`msg_cd`)와 메시지(`msg1`)
+    #[error("API 오류 [{code}]: {message}")]
+    Response { code: String, message: String },
+    #[error("주문을 찾을 수 없습니다: {0}")]
+    OrderNotFound(String),
+    /// 정정 주문은 접수되었지만 원주문에 남은 잔량을 취소하지 못함
+    #[error("정정 주문 {new_order_id}은 접수되었으나 원주문 {order_id}의 남은 잔량을 취소하지 못했습니다: {message}")]
+    RemainderNotCancelled { order_id: String, new_order_id: String, message: String },
+    /// 일괄 취소에서 취소하지 못한 주문번호 목록
+    #[error("주문을 취소하지 못했습니다: {}", .0.join(", "))]
+    CancelFailed(Vec<String>),
+    #[error("종목을 찾을 수 없습니다: {0}")]
+    StockNotFound(String),
+    /// 현재 시각까지 체결된 시세가 없음
+    #[error("{0} 종목의 시세가 아직 없습니다")]
+    NoPrice(String),
+    #[error("{0:?} 세션이 초기화되지 않았습니다")]
+    NoSession(ApiEnv),
+}
+
+/// 문자열 값 변환 실패 사유
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("정수 변환 실패: {0}")]
+    Int(#[from] ParseIntError),
+    #[error("실수 변환 실패: {0}")]
+    Float(#[from] ParseFloatError),
+    #[error("날짜/시각 변환 실패: {0}")]
+    DateTime(#[from] chrono::ParseError),
+    #[error("응답에 {0} 필드가 없습니다")]
+    MissingField(String),
+    #[error("실시간 데이터 복호화 실패: {0}")]
+    Decrypt(String),
+}
+
+impl From<ParseIntError> for StockrsError {
+    fn from(e: ParseIntError) -> Self {
+        StockrsError::Parse(e.into())
+    }
+}
+
+impl From<ParseFloatError> for StockrsError {
+    fn from(e: ParseFloatError) -> Self {
+        StockrsError::Parse(e.into())
+    }
+}
+
+impl From<chrono::ParseError> for StockrsError {
+    fn from(e: chrono::ParseError) -> Self {
+        StockrsError::Parse(e.into())
+    }
+}
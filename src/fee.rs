@@ -0,0 +1,124 @@
+use crate::types::broker::{BrokerType, OrderSide};
+use crate::types::market::Market;
+use chrono::NaiveDate;
+
+/// 한국투자증권 비대면 계좌 위탁수수료율 (유관기관 제비용 포함)
+const REAL_COMMISSION_RATE: f64 = 0.000_140_527;
+/// 한국투자증권 모의투자 위탁수수료율
+const PAPER_COMMISSION_RATE: f64 = 0.000_14;
+
+/// 농어촌특별세율 (KOSPI 매도분에만 부과)
+const RURAL_SPECIAL_TAX_RATE: f64 = 0.0015;
+
+/// 증권거래세율 변경 이력: (적용 시작일, KOSPI 세율, KOSDAQ 세율)
+///
+/// KOSPI 세율은 농어촌특별세를 제외한 증권거래세 부분입니다.
+const TRANSACTION_TAX_SCHEDULE: [((i32, u32, u32), f64, f64); 7] = [
+    ((1900, 1, 1), 0.0015, 0.0030),
+    ((2019, 6, 3), 0.0010, 0.0025),
+    ((2021, 1, 1), 0.0008, 0.0023),
+    ((2023, 1, 1), 0.0005, 0.0020),
+    ((2024, 1, 1), 0.0003, 0.0018),
+    ((2025, 1, 1), 0.0000, 0.0015),
+    ((2026, 1, 1), 0.0005, 0.0020),
+];
+
+/// 거래비용 모델
+///
+/// 위탁수수료는 브로커 종류별 요율을 매수·매도 모두에 적용하고,
+/// 증권거래세와 농어촌특별세는 매도 시 시장과 거래일에 맞는 세율로 부과합니다.
+/// ETF는 증권거래세와 농어촌특별세가 면제됩니다. 각 항목은 원 미만을 절사합니다.
+#[derive(Debug, Clone, Copy)]
+pub struct FeeModel {
+    commission_rate: f64,
+}
+
+impl FeeModel {
+    pub fn new(commission_rate: f64) -> Self {
+        Self { commission_rate }
+    }
+
+    /// 브로커 종류별 기본 수수료율을 사용하는 모델 (백테스트는 실계좌 요율 적용)
+    pub fn for_broker(kind: &BrokerType) -> Self {
+        match kind {
+            BrokerType::REAL | BrokerType::DB(_) => Self::new(REAL_COMMISSION_RATE),
+            BrokerType::PAPER => Self::new(PAPER_COMMISSION_RATE),
+        }
+    }
+
+    pub fn get_commission_rate(&self) -> f64 { self.commission_rate }
+
+    /// 위탁수수료
+    pub fn commission(&self, amount: f64) -> f64 {
+        (amount * self.commission_rate).floor()
+    }
+
+    /// 증권거래세 (매도 시)
+    pub fn transaction_tax(&self, side: OrderSide, market: Market, amount: f64, date: NaiveDate) -> f64 {
+        if side == OrderSide::Buy {
+            return 0.0;
+        }
+        (amount * transaction_tax_rate(market, date)).floor()
+    }
+
+    /// 농어촌특별세 (KOSPI 매도 시)
+    pub fn rural_special_tax(&self, side: OrderSide, market: Market, amount: f64) -> f64 {
+        match (side, market) {
+            (OrderSide::Sell, Market::KOSPI) => (amount * RURAL_SPECIAL_TAX_RATE).floor(),
+            _ => 0.0,
+        }
+    }
+
+    /// 체결 한 건의 총 거래비용 (수수료 + 증권거래세 + 농어촌특별세)
+    pub fn fee(&self, side: OrderSide, market: Market, price: f64, quantity: u32, date: NaiveDate) -> f64 {
+        let amount = price * quantity as f64;
+        self.commission(amount)
+            + self.transaction_tax(side, market, amount, date)
+            + self.rural_special_tax(side, market, amount)
+    }
+}
+
+/// 거래일에 적용되는 증권거래세율
+pub fn transaction_tax_rate(market: Market, date: NaiveDate) -> f64 {
+    let (_, kospi, kosdaq) = TRANSACTION_TAX_SCHEDULE
+        .iter()
+        .rev()
+        .find(|((y, m, d), _, _)| date >= NaiveDate::from_ymd_opt(*y, *m, *d).unwrap())
+        .unwrap_or(&TRANSACTION_TAX_SCHEDULE[0]);
+    match market {
+        Market::KOSPI => *kospi,
+        Market::KOSDAQ => *kosdaq,
+        Market::ETF => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_tax_rate_by_year() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(transaction_tax_rate(Market::KOSPI, date(2022, 12, 30)), 0.0008);
+        assert_eq!(transaction_tax_rate(Market::KOSPI, date(2024, 5, 2)), 0.0003);
+        assert_eq!(transaction_tax_rate(Market::KOSPI, date(2025, 7, 16)), 0.0);
+        assert_eq!(transaction_tax_rate(Market::KOSDAQ, date(2019, 6, 2)), 0.0030);
+        assert_eq!(transaction_tax_rate(Market::KOSDAQ, date(2025, 7, 16)), 0.0015);
+        assert_eq!(transaction_tax_rate(Market::ETF, date(2025, 7, 16)), 0.0);
+    }
+
+    #[test]
+    fn test_fee() {
+        let model = FeeModel::new(0.00015);
+        let date = NaiveDate::from_ymd_opt(2024, 7, 16).unwrap();
+
+        // 매수: 수수료만 (700만원 × 0.015% = 1050원)
+        assert_eq!(model.fee(OrderSide::Buy, Market::KOSPI, 70_000.0, 100, date), 1_050.0);
+        // KOSPI 매도: 수수료 1050 + 거래세 0.03% 2100 + 농특세 0.15% 10500
+        assert_eq!(model.fee(OrderSide::Sell, Market::KOSPI, 70_000.0, 100, date), 13_650.0);
+        // KOSDAQ 매도: 수수료 1050 + 거래세 0.18% 12600
+        assert_eq!(model.fee(OrderSide::Sell, Market::KOSDAQ, 70_000.0, 100, date), 13_650.0);
+        // ETF 매도: 수수료만
+        assert_eq!(model.fee(OrderSide::Sell, Market::ETF, 70_000.0, 100, date), 1_050.0);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::types::broker::{OrderSide, ValidationError};
+use crate::types::market::{Market, PriceInfo};
+use chrono::NaiveDate;
+
+/// 호가단위를 벗어난 가격의 처리 방식
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickPolicy {
+    /// 주문 거부
+    Reject,
+    /// 불리하지 않은 방향의 호가로 조정 (매수는 내림, 매도는 올림)
+    Round,
+}
+
+/// 일일 가격제한폭 (기준가 대비 ±30%)
+const PRICE_LIMIT_RATE: f64 = 0.3;
+
+/// KOSPI/KOSDAQ 호가단위가 통합된 날 (2023-01-25)
+fn unified_tick_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2023, 1, 25).unwrap()
+}
+
+/// 가격 수준, 시장, 적용일에 따른 호가단위
+///
+/// 2023-01-25부터는 두 시장이 같은 호가단위를 쓰고, 그 이전에는
+/// KOSDAQ이 5만원 이상 구간에서 100원 단위로 고정됩니다.
+/// ETF는 2천원 미만 1원, 그 이상 5원이며 2023-01-25 이전에는 모두 5원입니다.
+pub fn tick_size(price: f64, market: Market, date: NaiveDate) -> f64 {
+    if market == Market::ETF {
+        return match price {
+            p if p < 2_000.0 && date >= unified_tick_date() => 1.0,
+            _ => 5.0,
+        };
+    }
+    if date >= unified_tick_date() {
+        return match price {
+            p if p < 2_000.0 => 1.0,
+            p if p < 5_000.0 => 5.0,
+            p if p < 20_000.0 => 10.0,
+            p if p < 50_000.0 => 50.0,
+            p if p < 200_000.0 => 100.0,
+            p if p < 500_000.0 => 500.0,
+            _ => 1_000.0,
+        };
+    }
+
+    match (market, price) {
+        (_, p) if p < 1_000.0 => 1.0,
+        (_, p) if p < 5_000.0 => 5.0,
+        (_, p) if p < 10_000.0 => 10.0,
+        (_, p) if p < 50_000.0 => 50.0,
+        (Market::KOSDAQ, _) => 100.0,
+        (Market::KOSPI, p) if p < 100_000.0 => 100.0,
+        (Market::KOSPI, p) if p < 500_000.0 => 500.0,
+        (_, _) => 1_000.0,
+    }
+}
+
+/// 가격이 호가단위에 맞는지 확인
+pub fn is_on_tick(price: f64, market: Market, date: NaiveDate) -> bool {
+    let tick = tick_size(price, market, date);
+    (price / tick).fract() == 0.0
+}
+
+/// 가격을 호가단위에 맞게 조정합니다. 매수는 내림, 매도는 올림합니다.
+pub fn round_to_tick(price: f64, side: OrderSide, market: Market, date: NaiveDate) -> f64 {
+    let tick = tick_size(price, market, date);
+    let rounded = match side {
+        OrderSide::Buy => (price / tick).floor() * tick,
+        OrderSide::Sell => (price / tick).ceil() * tick,
+    };
+    // 올림으로 구간 경계를 넘으면 넘어간 구간의 호가단위로 다시 맞춤
+    let tick = tick_size(rounded, market, date);
+    (rounded / tick).ceil() * tick
+}
+
+/// 기준가로부터 계산한 (하한가, 상한가)
+///
+/// 상한가는 기준가의 130%를 호가단위로 내림, 하한가는 70%를 호가단위로 올림한 값입니다.
+pub fn price_limits(prev_close: f64, market: Market, date: NaiveDate) -> (f64, f64) {
+    let upper_raw = prev_close * (1.0 + PRICE_LIMIT_RATE);
+    let lower_raw = prev_close * (1.0 - PRICE_LIMIT_RATE);
+    let upper_tick = tick_size(upper_raw, market, date);
+    let lower_tick = tick_size(lower_raw, market, date);
+    ((lower_raw / lower_tick).ceil() * lower_tick, (upper_raw / upper_tick).floor() * upper_tick)
+}
+
+/// 지정가 주문 가격을 호가단위와 가격제한폭으로 검증하고, 주문에 사용할 가격을 반환합니다.
+///
+/// `TickPolicy::Round`이면 호가단위를 벗어난 가격을 조정한 뒤 가격제한폭을 검사합니다.
+pub fn validate_price(price: f64, side: OrderSide, info: &PriceInfo, date: NaiveDate, policy: TickPolicy) -> Result<f64, ValidationError> {
+    let market = info.get_market();
+    let price = if is_on_tick(price, market, date) {
+        price
+    } else {
+        match policy {
+            TickPolicy::Reject => {
+                return Err(ValidationError::InvalidTick { price, tick: tick_size(price, market, date) });
+            }
+            TickPolicy::Round => round_to_tick(price, side, market, date),
+        }
+    };
+
+    let (lower, upper) = price_limits(info.get_prev_close(), market, date);
+    if price < lower || price > upper {
+        return Err(ValidationError::OutOfPriceBand { price, lower, upper });
+    }
+    Ok(price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_tick_size_by_market_and_date() {
+        let before = date(2022, 12, 1);
+        let after = date(2025, 7, 16);
+
+        assert_eq!(tick_size(1_500.0, Market::KOSPI, before), 5.0);
+        assert_eq!(tick_size(1_500.0, Market::KOSPI, after), 1.0);
+        assert_eq!(tick_size(15_000.0, Market::KOSDAQ, before), 50.0);
+        assert_eq!(tick_size(15_000.0, Market::KOSDAQ, after), 10.0);
+
+        // 2023년 이전 KOSDAQ은 5만원 이상에서 100원 단위
+        assert_eq!(tick_size(600_000.0, Market::KOSDAQ, before), 100.0);
+        assert_eq!(tick_size(600_000.0, Market::KOSPI, before), 1_000.0);
+        assert_eq!(tick_size(150_000.0, Market::KOSPI, after), 100.0);
+        assert_eq!(tick_size(250_000.0, Market::KOSDAQ, after), 500.0);
+    }
+
+    #[test]
+    fn test_round_to_tick() {
+        let d = date(2025, 7, 16);
+        assert!(is_on_tick(70_100.0, Market::KOSPI, d));
+        assert!(!is_on_tick(70_150.0, Market::KOSPI, d));
+        assert_eq!(round_to_tick(70_150.0, OrderSide::Buy, Market::KOSPI, d), 70_100.0);
+        assert_eq!(round_to_tick(70_150.0, OrderSide::Sell, Market::KOSPI, d), 70_200.0);
+        // 19,995원 매도는 20,000원(50원 단위 구간)으로 올림
+        assert_eq!(round_to_tick(19_995.0, OrderSide::Sell, Market::KOSPI, d), 20_000.0);
+    }
+
+    #[test]
+    fn test_validate_price() {
+        let d = date(2025, 7, 16);
+        let info = PriceInfo::new(Market::KOSPI, 70_000.0);
+        assert_eq!(price_limits(70_000.0, Market::KOSPI, d), (49_000.0, 91_000.0));
+
+        assert_eq!(validate_price(70_100.0, OrderSide::Buy, &info, d, TickPolicy::Reject).unwrap(), 70_100.0);
+        assert!(matches!(
+            validate_price(70_150.0, OrderSide::Buy, &info, d, TickPolicy::Reject),
+            Err(ValidationError::InvalidTick { .. })
+        ));
+        assert_eq!(validate_price(70_150.0, OrderSide::Buy, &info, d, TickPolicy::Round).unwrap(), 70_100.0);
+        assert!(matches!(
+            validate_price(92_000.0, OrderSide::Buy, &info, d, TickPolicy::Round),
+            Err(ValidationError::OutOfPriceBand { .. })
+        ));
+    }
+}
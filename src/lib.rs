@@ -1,8 +1,15 @@
 pub mod types;
+pub mod error;
 pub mod api;
+pub mod stream;
 pub mod time;
 pub mod runner;
 pub mod data_reader;
 pub mod model;
 pub mod broker;
-pub mod db_manager;
\ No newline at end of file
+pub mod order_tracker;
+pub mod krx;
+pub mod fee;
+pub mod db_manager;
+pub mod price_db;
+pub mod downloader;
\ No newline at end of file
@@ -0,0 +1,80 @@
+use crate::error::StockrsError;
+use crate::stream::market_data::BarSet;
+use crate::time::TimeSignal;
+use crate::types::broker::Order;
+use crate::types::data_reader::DataReader;
+use crate::types::order_book::OrderBook;
+use crate::types::price::{Bar, BarInterval};
+use crate::types::trading::Holding;
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+
+/// 모델에 전달되는 시점별 시장 정보
+pub struct MarketSnapshot {
+    time: DateTime<Local>,
+    /// 이 시각까지 완성된 당일 분봉 (`MarketData`를 연결한 경우)
+    bars: BarSet,
+    /// 종목별 최신 호가 (`MarketData`에 호가 구독을 연결한 경우)
+    order_books: HashMap<String, OrderBook>,
+}
+
+impl MarketSnapshot {
+    pub fn new(time: DateTime<Local>) -> Self {
+        Self { time, bars: BarSet::new(), order_books: HashMap::new() }
+    }
+
+    pub fn with_bars(mut self, bars: BarSet) -> Self {
+        self.bars = bars;
+        self
+    }
+
+    pub fn with_order_books(mut self, order_books: HashMap<String, OrderBook>) -> Self {
+        self.order_books = order_books;
+        self
+    }
+
+    pub fn get_time(&self) -> DateTime<Local> { self.time }
+
+    /// 종목의 완성된 당일 분봉 (시간 오름차순)
+    pub fn get_bars(&self, stockcode: &str, interval: BarInterval) -> &[Bar] {
+        self.bars.get(&interval)
+            .and_then(|bars| bars.get(stockcode))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// 종목의 최신 호가
+    pub fn get_order_book(&self, stockcode: &str) -> Option<&OrderBook> {
+        self.order_books.get(stockcode)
+    }
+}
+
+/// 매매 전략이 구현해야 하는 인터페이스
+///
+/// `Runner`가 시간 이벤트마다 호출하며, 전략은 제출할 주문만 반환합니다.
+/// 주문 실행과 기록은 `Broker`와 `DBManager`가 담당하므로
+/// 전략을 바꿔도 브로커나 DB 코드는 건드릴 필요가 없습니다.
+pub trait Model {
+    /// 장 시작 전(DataPrep) 하루를 시작할 때 호출됩니다.
+    fn on_start_of_day(&mut self, _snapshot: &MarketSnapshot) -> Result<(), StockrsError> {
+        Ok(())
+    }
+
+    /// 장 시작(MarketOpen)과 장중 업데이트(Update)마다 호출되어
+    /// 제출할 주문 목록을 반환합니다.
+    ///
+    /// `holdings`는 `reader`로 조회한 보유 종목(주문가능수량 포함)이며,
+    /// 예수금과 현재가도 `reader`로 조회할 수 있습니다.
+    fn on_signal(
+        &mut self,
+        signal: TimeSignal,
+        snapshot: &MarketSnapshot,
+        holdings: &[Holding],
+        reader: &dyn DataReader,
+    ) -> Result<Vec<Order>, StockrsError>;
+
+    /// 장 종료(MarketClose) 후 하루를 마감할 때 호출됩니다.
+    fn on_end_of_day(&mut self, _snapshot: &MarketSnapshot) -> Result<(), StockrsError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,440 @@
+use crate::error::{ApiError, StockrsError};
+use crate::db_manager::DBManager;
+use crate::fee::FeeModel;
+use crate::types::broker::{Fill, Order, OrderInquiry, OrderStatus};
+use crate::types::market::Market;
+use chrono::{Duration, NaiveDateTime};
+use log::warn;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 시한이 지난 일부 체결 주문의 잔량 처리 방식
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemainderPolicy {
+    /// 잔량을 취소하고 `Expired`로 종료
+    Cancel,
+    /// 잔량을 장 종료(`expire_all`)까지 유지
+    KeepWorking,
+}
+
+/// 브로커에 제출된 주문과 그 진행 상태
+struct TrackedOrder {
+    order: Order,
+    /// 세금 계산에 쓰는 종목의 상장 시장
+    market: Market,
+    status: OrderStatus,
+    /// 기록된 누적 체결 수량
+    filled_quantity: u32,
+    /// 기록된 누적 체결분의 평균 체결가
+    avg_price: f64,
+    /// 체결통보로 전달받은 누적 체결 수량
+    notified_quantity: u32,
+    /// 이 시각까지 체결되지 않으면 취소 후 `Expired` 처리
+    deadline: NaiveDateTime,
+}
+
+/// 제출된 주문의 생애주기를 관리합니다.
+///
+/// `Submitted → PartiallyFilled → Filled` 순으로 진행되며, 거래소에서 취소/거부되거나
+/// 시한이 지나 취소되면 `Cancelled`/`Rejected`/`Expired`로 끝납니다.
+/// 체결 조회와 취소는 브로커가 넘겨주는 함수로 수행하므로 실거래/모의/백테스트가 같은 로직을 씁니다.
+///
+/// 체결은 발생할 때마다 체결 수량·가격으로 주문번호가 연결된 거래 내역을 한 건씩 저장하며,
+/// 수수료와 세금은 `FeeModel`로 체결분마다 계산합니다.
+pub struct OrderTracker {
+    default_timeout: Duration,
+    fee_model: FeeModel,
+    remainder_policy: RemainderPolicy,
+    orders: Mutex<HashMap<String, TrackedOrder>>,
+}
+
+impl OrderTracker {
+    pub fn new(default_timeout: Duration, fee_model: FeeModel) -> Self {
+        Self { default_timeout, fee_model, remainder_policy: RemainderPolicy::Cancel, orders: Mutex::new(HashMap::new()) }
+    }
+
+    /// 시한이 지난 일부 체결 주문의 잔량 처리 방식을 지정합니다.
+    pub fn with_remainder_policy(mut self, policy: RemainderPolicy) -> Self {
+        self.remainder_policy = policy;
+        self
+    }
+
+    /// 제출된 주문을 추적 대상으로 등록합니다.
+    ///
+    /// 시한은 주문 시각(`order.date`)에 주문별 `timeout`(없으면 기본값)을 더한 시각입니다.
+    pub fn register(&self, order_id: String, order: &Order, market: Market) {
+        let deadline = order.date + order.timeout.unwrap_or(self.default_timeout);
+        self.orders.lock().unwrap().insert(order_id, TrackedOrder {
+            order: order.clone(),
+            market,
+            status: OrderStatus::Submitted,
+            filled_quantity: 0,
+            avg_price: 0.0,
+            notified_quantity: 0,
+            deadline,
+        });
+    }
+
+    pub fn status(&self, order_id: &str) -> Option<OrderStatus> {
+        self.orders.lock().unwrap().get(order_id).map(|tracked| tracked.status)
+    }
+
+    /// 누적 체결 수량을 반환합니다.
+    pub fn filled_quantity(&self, order_id: &str) -> Option<u32> {
+        self.orders.lock().unwrap().get(order_id).map(|tracked| tracked.filled_quantity)
+    }
+
+    /// 진행 중인 모든 주문의 체결 상태를 조회하여 갱신합니다.
+    ///
+    /// 새로 체결된 수량이 있으면 거래 내역으로 저장하고, `now`가 시한을 지난 주문은
+    /// 잔량 처리 방식에 따라 취소 후 `Expired`로 바꿉니다.
+    /// 모든 주문을 먼저 조회한 뒤 새 체결이 있으면 데이터 리더 캐시를 한 번 무효화하고 기록하므로,
+    /// 여러 체결의 기록이 같은 계좌 조회 결과를 사용합니다.
+    /// 조회나 취소에 실패한 주문은 로그를 남기고 건너뛰며, 다음 조회에서 다시 처리합니다.
+    pub fn poll<F, C>(&self, db: &DBManager, now: NaiveDateTime, mut check: F, mut cancel: C) -> Result<(), StockrsError>
+    where
+        F: FnMut(&str) -> Result<OrderInquiry, StockrsError>,
+        C: FnMut(&str) -> Result<(), StockrsError>,
+    {
+        let mut orders = self.orders.lock().unwrap();
+        let mut inquiries = HashMap::new();
+        let mut has_new_fill = false;
+        for (order_id, tracked) in orders.iter().filter(|(_, t)| !t.status.is_terminal()) {
+            let inquiry = match check(order_id) {
+                Ok(inquiry) => inquiry,
+                Err(e) => {
+                    warn!("주문 {} 체결 조회 실패: {}", order_id, e);
+                    continue;
+                }
+            };
+            has_new_fill |= inquiry.filled_quantity > tracked.filled_quantity;
+            inquiries.insert(order_id.clone(), inquiry);
+        }
+        if has_new_fill {
+            db.invalidate_account();
+        }
+
+        for (order_id, tracked) in orders.iter_mut() {
+            let Some(inquiry) = inquiries.remove(order_id) else { continue };
+            self.apply_inquiry(db, order_id, tracked, inquiry, now)?;
+
+            let keep = self.remainder_policy == RemainderPolicy::KeepWorking
+                && tracked.status == OrderStatus::PartiallyFilled;
+            if !tracked.status.is_terminal() && now >= tracked.deadline && !keep {
+                match cancel(order_id) {
+                    Ok(()) => tracked.status = OrderStatus::Expired,
+                    Err(e) => warn!("시한이 지난 주문 {} 취소 실패: {}", order_id, e),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 외부에서 전달된 체결 한 건을 반영하고 거래 내역으로 저장합니다.
+    ///
+    /// 추적하지 않는 주문번호의 체결이면 `false`를 반환합니다.
+    pub fn apply_fill(&self, db: &DBManager, fill: &Fill) -> Result<bool, StockrsError> {
+        let mut orders = self.orders.lock().unwrap();
+        let tracked = match orders.get_mut(&fill.order_id) {
+            Some(tracked) => tracked,
+            None => return Ok(false),
+        };
+        self.record(db, tracked, fill)?;
+        Ok(true)
+    }
+
+    /// 체결통보 한 건을 반영합니다.
+    ///
+    /// 같은 체결이 체결 조회(`poll`)로 먼저 기록되었을 수 있으므로, 통보받은 누적 수량이
+    /// 이미 기록된 누적 수량을 넘는 만큼만 통보 가격으로 저장합니다.
+    /// 추적하지 않는 주문번호의 통보면 `false`를 반환합니다.
+    pub fn apply_notice(&self, db: &DBManager, fill: &Fill) -> Result<bool, StockrsError> {
+        let mut orders = self.orders.lock().unwrap();
+        let tracked = match orders.get_mut(&fill.order_id) {
+            Some(tracked) => tracked,
+            None => return Ok(false),
+        };
+        tracked.notified_quantity += fill.quantity;
+        if tracked.notified_quantity > tracked.filled_quantity {
+            let quantity = tracked.notified_quantity - tracked.filled_quantity;
+            self.record(db, tracked, &Fill { quantity, ..fill.clone() })?;
+        }
+        Ok(true)
+    }
+
+    /// 진행 중인 주문과 그 누적 체결 수량을 반환합니다. 종료된 주문이면 `None`입니다.
+    pub fn active_order(&self, order_id: &str) -> Option<(Order, u32)> {
+        let orders = self.orders.lock().unwrap();
+        let tracked = orders.get(order_id).filter(|t| !t.status.is_terminal())?;
+        Some((tracked.order.clone(), tracked.filled_quantity))
+    }
+
+    /// 정정으로 원주문의 잔량이 새 주문번호로 옮겨졌음을 반영합니다.
+    ///
+    /// 원주문은 `Cancelled`로 끝나고, 정정 주문은 원주문의 시한과 시장을 이어받아 새로 추적합니다.
+    /// 원주문을 추적하고 있지 않으면 `ApiError::OrderNotFound`를 반환합니다.
+    pub fn replace(&self, order_id: &str, new_order_id: String, amended: Order) -> Result<(), StockrsError> {
+        let mut orders = self.orders.lock().unwrap();
+        let tracked = orders.get_mut(order_id).ok_or_else(|| ApiError::OrderNotFound(order_id.to_string()))?;
+        tracked.status = OrderStatus::Cancelled;
+        let (deadline, market) = (tracked.deadline, tracked.market);
+        orders.insert(new_order_id, TrackedOrder {
+            order: amended,
+            market,
+            status: OrderStatus::Submitted,
+            filled_quantity: 0,
+            avg_price: 0.0,
+            notified_quantity: 0,
+            deadline,
+        });
+        Ok(())
+    }
+
+    /// 정정으로 `Cancelled` 처리한 원주문을 다시 진행 중으로 되돌립니다. (남은 잔량의 취소에 실패한 경우)
+    pub fn reactivate(&self, order_id: &str) {
+        if let Some(tracked) = self.orders.lock().unwrap().get_mut(order_id) {
+            tracked.status = match tracked.filled_quantity {
+                0 => OrderStatus::Submitted,
+                _ => OrderStatus::PartiallyFilled,
+            };
+        }
+    }
+
+    /// 진행 중인 모든 주문을 취소하고 `Expired`로 바꿉니다.
+    ///
+    /// 취소에 실패한 주문은 상태를 유지한 채 나머지 주문을 계속 취소하고,
+    /// 실패한 주문번호를 모아 `ApiError::CancelFailed`로 반환합니다.
+    pub fn expire_all<C>(&self, mut cancel: C) -> Result<(), StockrsError>
+    where
+        C: FnMut(&str) -> Result<(), StockrsError>,
+    {
+        let mut orders = self.orders.lock().unwrap();
+        let mut failed = Vec::new();
+        for (order_id, tracked) in orders.iter_mut().filter(|(_, t)| !t.status.is_terminal()) {
+            match cancel(order_id) {
+                Ok(()) => tracked.status = OrderStatus::Expired,
+                Err(e) => {
+                    warn!("주문 {} 취소 실패: {}", order_id, e);
+                    failed.push(order_id.clone());
+                }
+            }
+        }
+        match failed.is_empty() {
+            true => Ok(()),
+            false => {
+                failed.sort();
+                Err(ApiError::CancelFailed(failed).into())
+            }
+        }
+    }
+
+    /// 누적 체결 조회 결과를 이전 기록과 비교하여 새 체결분을 저장하고 상태를 갱신합니다.
+    ///
+    /// 체결 시각은 조회 결과의 마지막 체결 시각이며, 없으면 `now`를 씁니다.
+    fn apply_inquiry(&self, db: &DBManager, order_id: &str, tracked: &mut TrackedOrder, inquiry: OrderInquiry, now: NaiveDateTime) -> Result<(), StockrsError> {
+        if inquiry.filled_quantity > tracked.filled_quantity {
+            let quantity = inquiry.filled_quantity - tracked.filled_quantity;
+            // 누적 체결금액 차이로 이번 체결분의 평균가 계산
+            let amount = inquiry.avg_price * inquiry.filled_quantity as f64
+                - tracked.avg_price * tracked.filled_quantity as f64;
+            let fill = Fill { order_id: order_id.to_string(), quantity, price: amount / quantity as f64, time: inquiry.filled_at.unwrap_or(now) };
+            self.record(db, tracked, &fill)?;
+        }
+        if inquiry.status.is_terminal() && tracked.status != OrderStatus::Filled {
+            tracked.status = inquiry.status;
+        }
+        Ok(())
+    }
+
+    /// 체결 한 건을 거래 내역으로 저장하고 누적 체결 정보와 상태를 갱신합니다.
+    fn record(&self, db: &DBManager, tracked: &mut TrackedOrder, fill: &Fill) -> Result<(), StockrsError> {
+        let order = &tracked.order;
+        let fee = self.fee_model.fee(order.side, tracked.market, fill.price, fill.quantity, fill.time.date());
+        db.save_trading(order.fill_to_trading(fill, fee))?;
+
+        let total = tracked.filled_quantity + fill.quantity;
+        tracked.avg_price = (tracked.avg_price * tracked.filled_quantity as f64 + fill.price * fill.quantity as f64) / total as f64;
+        tracked.filled_quantity = total;
+        tracked.status = if total >= tracked.order.quantity {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::db_api::DbApi;
+    use crate::price_db::PriceDB;
+    use crate::time::SimulatedClock;
+    use crate::types::broker::{test_order, OrderSide, OrderType};
+    use crate::types::data_reader::DataReader;
+    use crate::types::price::Bar;
+    use crate::types::trading::{AssetInfo, Holding};
+    use chrono::{Local, NaiveDate, TimeZone};
+    use std::path::PathBuf;
+
+    struct FixedReader;
+    impl DataReader for FixedReader {
+        fn get_asset_info(&self) -> Result<AssetInfo, StockrsError> {
+            Ok(AssetInfo::new(NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 0, 0).unwrap(), 0.0))
+        }
+        fn get_avg_price(&self, _stockcode: String) -> Result<f64, StockrsError> {
+            Ok(70000.0)
+        }
+        fn get_holdings(&self) -> Result<Vec<Holding>, StockrsError> {
+            Ok(Vec::new())
+        }
+        fn get_orderable_cash(&self) -> Result<f64, StockrsError> {
+            Ok(0.0)
+        }
+        fn get_current_price(&self, _stockcode: &str) -> Result<f64, StockrsError> {
+            Ok(70000.0)
+        }
+    }
+
+    #[test]
+    fn test_order_lifecycle_fill_and_expire() {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap();
+        let prices = PriceDB::new(PathBuf::from(":memory:")).unwrap();
+        // 09:03 분봉에서만 가격이 70000 아래로 내려감
+        for minute in 0..10 {
+            let low = if minute == 3 { 69500.0 } else { 70500.0 };
+            let bar = Bar::new(date.and_hms_opt(9, minute, 0).unwrap(), 71000.0, 71500.0, low, 71000.0, 100);
+            prices.upsert_minute_bar("005930", &bar).unwrap();
+        }
+        let clock = SimulatedClock::new(Local.with_ymd_and_hms(2025, 7, 16, 9, 0, 0).unwrap());
+        let api = DbApi::new(prices, clock.clone(), 1_000_000.0);
+        let db = DBManager::with_data_reader(PathBuf::from(":memory:"), Box::new(FixedReader)).unwrap();
+        let tracker = OrderTracker::new(Duration::minutes(5), FeeModel::new(0.0));
+
+        let start = date.and_hms_opt(9, 0, 0).unwrap();
+        let resting_order = test_order(start, OrderSide::Buy, OrderType::Limit, 1, 70000.0);
+        let short_order = Order { timeout: Some(Duration::minutes(2)), ..test_order(start, OrderSide::Buy, OrderType::Limit, 1, 69000.0) };
+        let resting = api.execute_order(&resting_order).unwrap();
+        let short = api.execute_order(&short_order).unwrap();
+        tracker.register(resting.clone(), &resting_order, Market::KOSPI);
+        tracker.register(short.clone(), &short_order, Market::KOSPI);
+
+        for minute in 0..=3 {
+            clock.set(Local.with_ymd_and_hms(2025, 7, 16, 9, minute, 0).unwrap());
+            tracker.poll(&db, api.now(), |id| api.check_fill(id), |id| api.cancel_order(id)).unwrap();
+        }
+
+        assert_eq!(tracker.status(&resting), Some(OrderStatus::Filled));
+        assert_eq!(tracker.status(&short), Some(OrderStatus::Expired));
+        assert_eq!(db.holdings().unwrap()[0].get_quantity(), 1);
+    }
+
+    #[test]
+    fn test_partial_fills_recorded_per_fill() {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap();
+        let prices = PriceDB::new(PathBuf::from(":memory:")).unwrap();
+        // 분봉 거래량이 4주뿐이라 10주 주문은 접수 후 분봉마다 4주씩 체결
+        for minute in 0..10 {
+            let bar = Bar::new(date.and_hms_opt(9, minute, 0).unwrap(), 70000.0, 70500.0, 69500.0, 70000.0, 4);
+            prices.upsert_minute_bar("005930", &bar).unwrap();
+        }
+        let clock = SimulatedClock::new(Local.with_ymd_and_hms(2025, 7, 16, 9, 0, 0).unwrap());
+        let api = DbApi::new(prices, clock.clone(), 1_000_000.0);
+        let db = DBManager::with_data_reader(PathBuf::from(":memory:"), Box::new(FixedReader)).unwrap();
+        let tracker = OrderTracker::new(Duration::minutes(5), FeeModel::new(0.0));
+
+        let start = date.and_hms_opt(9, 0, 0).unwrap();
+        let order = Order { timeout: Some(Duration::minutes(2)), ..test_order(start, OrderSide::Buy, OrderType::Limit, 10, 70000.0) };
+        let order_id = api.execute_order(&order).unwrap();
+        tracker.register(order_id.clone(), &order, Market::KOSPI);
+
+        for minute in 0..=2 {
+            clock.set(Local.with_ymd_and_hms(2025, 7, 16, 9, minute, 0).unwrap());
+            tracker.poll(&db, api.now(), |id| api.check_fill(id), |id| api.cancel_order(id)).unwrap();
+            // 같은 분봉에서 다시 조회해도 추가 체결 없음
+            tracker.poll(&db, api.now(), |id| api.check_fill(id), |id| api.cancel_order(id)).unwrap();
+        }
+
+        assert_eq!(tracker.status(&order_id), Some(OrderStatus::Expired));
+        assert_eq!(tracker.filled_quantity(&order_id), Some(8));
+        assert_eq!(db.holdings().unwrap()[0].get_quantity(), 8);
+    }
+
+    #[test]
+    fn test_keep_working_remainder() {
+        let db = DBManager::with_data_reader(PathBuf::from(":memory:"), Box::new(FixedReader)).unwrap();
+        let tracker = OrderTracker::new(Duration::minutes(1), FeeModel::new(0.0)).with_remainder_policy(RemainderPolicy::KeepWorking);
+        let start = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        tracker.register("1".to_string(), &test_order(start, OrderSide::Buy, OrderType::Limit, 10, 70000.0), Market::KOSPI);
+
+        let partial = |_: &str| Ok(OrderInquiry { status: OrderStatus::PartiallyFilled, filled_quantity: 3, avg_price: 69900.0, filled_at: None });
+        tracker.poll(&db, start + Duration::minutes(2), partial, |_| Ok(())).unwrap();
+        assert_eq!(tracker.status("1"), Some(OrderStatus::PartiallyFilled));
+
+        tracker.apply_fill(&db, &Fill { order_id: "1".to_string(), quantity: 7, price: 70000.0, time: start + Duration::minutes(3) }).unwrap();
+        assert_eq!(tracker.status("1"), Some(OrderStatus::Filled));
+        assert_eq!(db.holdings().unwrap()[0].get_quantity(), 10);
+    }
+
+    #[test]
+    fn test_notice_and_poll_record_once() {
+        let db = DBManager::with_data_reader(PathBuf::from(":memory:"), Box::new(FixedReader)).unwrap();
+        let tracker = OrderTracker::new(Duration::minutes(10), FeeModel::new(0.0));
+        let start = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        tracker.register("1".to_string(), &test_order(start, OrderSide::Buy, OrderType::Limit, 10, 70000.0), Market::KOSPI);
+        let notice = |quantity| Fill { order_id: "1".to_string(), quantity, price: 70000.0, time: start + Duration::minutes(1) };
+
+        // 체결 조회가 먼저 3주를 기록한 뒤 같은 체결의 통보가 도착
+        let polled = |_: &str| Ok(OrderInquiry { status: OrderStatus::PartiallyFilled, filled_quantity: 3, avg_price: 70000.0, filled_at: None });
+        tracker.poll(&db, start + Duration::minutes(1), polled, |_| Ok(())).unwrap();
+        tracker.apply_notice(&db, &notice(3)).unwrap();
+        assert_eq!(tracker.filled_quantity("1"), Some(3));
+
+        // 통보가 먼저 도착한 체결은 이후 조회에서 다시 기록하지 않음
+        tracker.apply_notice(&db, &notice(7)).unwrap();
+        let filled = |_: &str| Ok(OrderInquiry { status: OrderStatus::Filled, filled_quantity: 10, avg_price: 70000.0, filled_at: None });
+        tracker.poll(&db, start + Duration::minutes(2), filled, |_| Ok(())).unwrap();
+        assert_eq!(tracker.status("1"), Some(OrderStatus::Filled));
+        assert_eq!(db.holdings().unwrap()[0].get_quantity(), 10);
+    }
+
+    #[test]
+    fn test_failed_inquiry_skips_only_that_order() {
+        let db = DBManager::with_data_reader(PathBuf::from(":memory:"), Box::new(FixedReader)).unwrap();
+        let tracker = OrderTracker::new(Duration::minutes(10), FeeModel::new(0.0));
+        let start = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        tracker.register("1".to_string(), &test_order(start, OrderSide::Buy, OrderType::Limit, 10, 70000.0), Market::KOSPI);
+        tracker.register("2".to_string(), &test_order(start, OrderSide::Buy, OrderType::Limit, 10, 70000.0), Market::KOSPI);
+
+        // 1번 주문의 조회가 실패해도 2번 주문의 체결은 조회 결과의 체결 시각으로 기록
+        let filled_at = start + Duration::seconds(30);
+        let check = |order_id: &str| match order_id {
+            "1" => Err(ApiError::OrderNotFound(order_id.to_string()).into()),
+            _ => Ok(OrderInquiry { status: OrderStatus::Filled, filled_quantity: 10, avg_price: 70000.0, filled_at: Some(filled_at) }),
+        };
+        tracker.poll(&db, start + Duration::minutes(1), check, |_| Ok(())).unwrap();
+        assert_eq!(tracker.status("1"), Some(OrderStatus::Submitted));
+        assert_eq!(tracker.status("2"), Some(OrderStatus::Filled));
+        assert_eq!(db.position("005930").unwrap().unwrap().get_first_entry(), Some(filled_at));
+    }
+
+    #[test]
+    fn test_expire_all_tries_every_order() {
+        let tracker = OrderTracker::new(Duration::minutes(10), FeeModel::new(0.0));
+        let start = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        for order_id in ["1", "2", "3"] {
+            tracker.register(order_id.to_string(), &test_order(start, OrderSide::Buy, OrderType::Limit, 1, 70000.0), Market::KOSPI);
+        }
+
+        // 2번 주문의 취소가 실패해도 나머지 주문은 취소하고, 실패한 주문만 진행 중으로 남김
+        let cancel = |order_id: &str| match order_id {
+            "2" => Err(ApiError::OrderNotFound(order_id.to_string()).into()),
+            _ => Ok(()),
+        };
+        let err = tracker.expire_all(cancel).unwrap_err();
+        assert!(matches!(err, StockrsError::Api(ApiError::CancelFailed(ref ids)) if ids == &["2".to_string()]));
+        assert_eq!(tracker.status("1"), Some(OrderStatus::Expired));
+        assert_eq!(tracker.status("2"), Some(OrderStatus::Submitted));
+        assert_eq!(tracker.status("3"), Some(OrderStatus::Expired));
+    }
+}
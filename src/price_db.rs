@@ -0,0 +1,264 @@
+use rusqlite::{Connection, OptionalExtension};
+use crate::error::StockrsError;
+use crate::types::market::Market;
+use crate::types::price::Bar;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::path::PathBuf;
+
+/// 백테스트에 사용하는 과거 시세 데이터베이스
+///
+/// `minute_price` 테이블에 종목별 1분봉을 `YYYY-MM-DD HH:MM:SS` 문자열 시각으로, `daily_price` 테이블에
+/// 일봉을 `YYYY-MM-DD` 문자열 날짜로 저장하고, `stock_info` 테이블에 종목별 상장 시장을 저장합니다.
+/// `download_progress` 테이블은 시세 다운로드가 중단되었을 때 이어받을 위치를 기록합니다.
+///
+/// 테이블 이름이 거래 기록(`DBManager`)과 겹치지 않으므로 같은 파일을 함께 사용해도 됩니다.
+pub struct PriceDB {
+    conn: Connection,
+}
+
+impl PriceDB {
+    pub fn new(path: PathBuf) -> Result<Self, StockrsError> {
+        let conn = Connection::open(path)?;
+
+        // Create minute price table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS minute_price (
+                stockcode TEXT,
+                datetime TEXT,
+                open REAL,
+                high REAL,
+                low REAL,
+                close REAL,
+                volume INTEGER,
+                PRIMARY KEY (stockcode, datetime)
+            )",
+            (),
+        )?;
+
+        // Create daily price table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS daily_price (
+                stockcode TEXT,
+                date TEXT,
+                open REAL,
+                high REAL,
+                low REAL,
+                close REAL,
+                volume INTEGER,
+                PRIMARY KEY (stockcode, date)
+            )",
+            (),
+        )?;
+
+        // Create download progress table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS download_progress (
+                stockcode TEXT,
+                kind TEXT,
+                first TEXT,
+                last TEXT,
+                done INTEGER,
+                PRIMARY KEY (stockcode, kind)
+            )",
+            (),
+        )?;
+
+        // Create stock info table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS stock_info (
+                stockcode TEXT PRIMARY KEY,
+                market TEXT
+            )",
+            (),
+        )?;
+
+        Ok(Self { conn })
+    }
+
+    // Insert or replace a minute bar
+    pub fn upsert_minute_bar(&self, stockcode: &str, bar: &Bar) -> Result<(), StockrsError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO minute_price (stockcode, datetime, open, high, low, close, volume)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            (
+                stockcode,
+                bar.get_time().to_string(),
+                bar.get_open(),
+                bar.get_high(),
+                bar.get_low(),
+                bar.get_close(),
+                bar.get_volume() as i64,
+            ),
+        )?;
+        Ok(())
+    }
+
+    // Insert or replace a daily bar (the bar time is the trading date at midnight)
+    pub fn upsert_daily_bar(&self, stockcode: &str, bar: &Bar) -> Result<(), StockrsError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO daily_price (stockcode, date, open, high, low, close, volume)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            (
+                stockcode,
+                bar.get_time().date().to_string(),
+                bar.get_open(),
+                bar.get_high(),
+                bar.get_low(),
+                bar.get_close(),
+                bar.get_volume() as i64,
+            ),
+        )?;
+        Ok(())
+    }
+
+    // Get the daily bar of the given date
+    pub fn get_daily_bar(&self, stockcode: &str, date: NaiveDate) -> Result<Option<Bar>, StockrsError> {
+        Ok(self.conn
+            .query_row(
+                "SELECT date || ' 00:00:00', open, high, low, close, volume FROM daily_price
+                 WHERE stockcode = ? AND date = ?",
+                (stockcode, date.to_string()),
+                Self::row_to_bar,
+            )
+            .optional()?)
+    }
+
+    /// 받은 봉 한 페이지와 다운로드 진행 위치를 한 트랜잭션으로 저장합니다.
+    ///
+    /// 중간에 중단되어도 저장된 진행 위치까지의 봉은 항상 DB에 있으므로 그 위치부터 이어받을 수 있습니다.
+    pub fn save_download_page(&self, stockcode: &str, kind: PriceKind, bars: &[Bar], progress: &DownloadProgress) -> Result<(), StockrsError> {
+        let tx = self.conn.unchecked_transaction()?;
+        for bar in bars {
+            match kind {
+                PriceKind::Minute => self.upsert_minute_bar(stockcode, bar)?,
+                PriceKind::Daily => self.upsert_daily_bar(stockcode, bar)?,
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO download_progress (stockcode, kind, first, last, done) VALUES (?, ?, ?, ?, ?)",
+            (stockcode, kind.as_str(), progress.first.to_string(), progress.last.to_string(), progress.done),
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    // Get the download progress of a stock
+    pub fn get_download_progress(&self, stockcode: &str, kind: PriceKind) -> Result<Option<DownloadProgress>, StockrsError> {
+        let row: Option<(String, String, bool)> = self.conn
+            .query_row(
+                "SELECT first, last, done FROM download_progress WHERE stockcode = ? AND kind = ?",
+                (stockcode, kind.as_str()),
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        row.map(|(first, last, done)| {
+            Ok(DownloadProgress {
+                first: NaiveDateTime::parse_from_str(&first, "%Y-%m-%d %H:%M:%S")?,
+                last: NaiveDateTime::parse_from_str(&last, "%Y-%m-%d %H:%M:%S")?,
+                done,
+            })
+        })
+        .transpose()
+    }
+
+    // Get the first minute bar starting after `after` and no later than `until`
+    pub fn get_next_minute_bar(&self, stockcode: &str, after: NaiveDateTime, until: NaiveDateTime) -> Result<Option<Bar>, StockrsError> {
+        Ok(self.conn
+            .query_row(
+                "SELECT datetime, open, high, low, close, volume FROM minute_price
+                 WHERE stockcode = ? AND datetime > ? AND datetime <= ?
+                 ORDER BY datetime LIMIT 1",
+                (stockcode, after.to_string(), until.to_string()),
+                Self::row_to_bar,
+            )
+            .optional()?)
+    }
+
+    // Get the close of the latest bar starting before the given time (the bar at `time` is not complete yet)
+    pub fn get_last_close(&self, stockcode: &str, time: NaiveDateTime) -> Result<Option<f64>, StockrsError> {
+        Ok(self.conn
+            .query_row(
+                "SELECT close FROM minute_price
+                 WHERE stockcode = ? AND datetime < ?
+                 ORDER BY datetime DESC LIMIT 1",
+                (stockcode, time.to_string()),
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    // Set the listing market of a stock
+    pub fn set_market(&self, stockcode: &str, market: Market) -> Result<(), StockrsError> {
+        let market = match market {
+            Market::KOSPI => "KOSPI",
+            Market::KOSDAQ => "KOSDAQ",
+            Market::ETF => "ETF",
+        };
+        self.conn.execute(
+            "INSERT OR REPLACE INTO stock_info (stockcode, market) VALUES (?, ?)",
+            (stockcode, market),
+        )?;
+        Ok(())
+    }
+
+    // Get the listing market of a stock (KOSPI if not recorded)
+    pub fn get_market(&self, stockcode: &str) -> Result<Market, StockrsError> {
+        let market: Option<String> = self.conn
+            .query_row(
+                "SELECT market FROM stock_info WHERE stockcode = ?",
+                [stockcode],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(match market.as_deref() {
+            Some("KOSDAQ") => Market::KOSDAQ,
+            Some("ETF") => Market::ETF,
+            _ => Market::KOSPI,
+        })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn count_bars(&self, stockcode: &str, kind: PriceKind) -> Result<usize, StockrsError> {
+        let table = match kind {
+            PriceKind::Minute => "minute_price",
+            PriceKind::Daily => "daily_price",
+        };
+        let count: i64 = self.conn.query_row(&format!("SELECT COUNT(*) FROM {} WHERE stockcode = ?", table), [stockcode], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    fn row_to_bar(row: &rusqlite::Row) -> rusqlite::Result<Bar> {
+        let datetime: String = row.get(0)?;
+        let time = NaiveDateTime::parse_from_str(&datetime, "%Y-%m-%d %H:%M:%S")
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
+        let volume: i64 = row.get(5)?;
+        Ok(Bar::new(time, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, volume as u64))
+    }
+}
+
+/// 다운로드하는 시세 종류
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceKind {
+    Minute,
+    Daily,
+}
+
+impl PriceKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PriceKind::Minute => "minute",
+            PriceKind::Daily => "daily",
+        }
+    }
+}
+
+/// 종목별로 이어서 받은 시세 구간
+///
+/// `first`부터 `last`까지의 봉은 빠짐없이 저장되어 있습니다. 일봉은 날짜의 자정 시각을 사용합니다.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DownloadProgress {
+    pub first: NaiveDateTime,
+    pub last: NaiveDateTime,
+    /// 더 과거의 시세가 없음 (분봉은 장 시작, 일봉은 상장일까지 받음)
+    pub done: bool,
+}
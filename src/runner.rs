@@ -0,0 +1,252 @@
+use crate::error::StockrsError;
+use crate::db_manager::DBManager;
+use crate::model::{MarketSnapshot, Model};
+use crate::stream::market_data::MarketData;
+use crate::time::{Clock, TimeService, TimeSignal, WallClock};
+use crate::types::broker::Broker;
+use chrono::{DateTime, Local};
+use log::{error, info};
+
+/// `TimeService`, `Broker`, `DBManager`, `Model`을 묶어 하루 매매 흐름을 구동합니다.
+///
+/// 시그널별 동작:
+/// - DataPrep: 모델의 하루 시작 훅 호출
+/// - MarketOpen: 당일 overview 생성 후 모델 주문 실행
+/// - Update: 미체결 주문 갱신, 모델 주문 실행 후 overview 갱신
+/// - MarketClose: 남은 미체결 주문 취소, 당일 overview 마감 후 모델의 하루 종료 훅 호출
+/// - Overnight: 다음 거래일까지 대기
+///
+/// `MarketData`를 연결하면 각 시그널 시각까지 완성된 분봉과 최신 호가를 스냅샷에 담아 모델에 전달합니다.
+pub struct Runner<C: Clock = WallClock> {
+    time: TimeService<C>,
+    broker: Box<dyn Broker>,
+    db: DBManager,
+    model: Box<dyn Model>,
+    market_data: Option<MarketData>,
+    /// 모델의 하루 시작 훅이 호출되었는지 여부
+    day_started: bool,
+    /// 당일 overview가 생성되었는지 여부
+    overview_ready: bool,
+}
+
+impl<C: Clock> Runner<C> {
+    pub fn new(time: TimeService<C>, broker: Box<dyn Broker>, db: DBManager, model: Box<dyn Model>) -> Self {
+        Self { time, broker, db, model, market_data: None, day_started: false, overview_ready: false }
+    }
+
+    /// 실시간 분봉을 모델에 제공할 `MarketData`를 연결합니다.
+    pub fn with_market_data(mut self, market_data: MarketData) -> Self {
+        self.market_data = Some(market_data);
+        self
+    }
+
+    /// 종료 없이 이벤트 루프를 계속 실행합니다.
+    pub fn run(&mut self) -> Result<(), StockrsError> {
+        loop {
+            self.step()?;
+        }
+    }
+
+    /// `end` 시각 이전의 이벤트를 모두 처리한 뒤 반환합니다. (백테스트용)
+    pub fn run_until(&mut self, end: DateTime<Local>) -> Result<(), StockrsError> {
+        while self.time.now() < end {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    /// 현재 이벤트 시각까지 대기한 뒤 해당 시그널을 처리하고,
+    /// 내부 시간을 다음 이벤트로 이동합니다.
+    pub fn step(&mut self) -> Result<TimeSignal, StockrsError> {
+        let target = self.time.now();
+        let signal = self.time.now_signal();
+        self.time.wait_until(target);
+        self.handle(signal)?;
+        self.time.advance();
+        Ok(signal)
+    }
+
+    fn handle(&mut self, signal: TimeSignal) -> Result<(), StockrsError> {
+        let now = self.time.now();
+        let snapshot = self.snapshot(now);
+        match signal {
+            TimeSignal::DataPrep => {
+                info!("[{}] 데이터 준비", now);
+                self.start_day(&snapshot)?;
+            }
+            TimeSignal::MarketOpen => {
+                info!("[{}] 장 시작", now);
+                self.start_day(&snapshot)?;
+                self.open_overview()?;
+                self.trade(signal, &snapshot)?;
+            }
+            TimeSignal::Update => {
+                // 장중에 시작한 경우 하루 시작 처리를 먼저 수행
+                self.start_day(&snapshot)?;
+                self.open_overview()?;
+                if let Err(e) = self.broker.poll(&self.db) {
+                    error!("[{}] 체결 조회 실패: {}", now, e);
+                }
+                self.trade(signal, &snapshot)?;
+                self.db.update_overview()?;
+            }
+            TimeSignal::MarketClose => {
+                info!("[{}] 장 종료", now);
+                if let Err(e) = self.broker.poll(&self.db) {
+                    error!("[{}] 체결 조회 실패: {}", now, e);
+                }
+                if let Err(e) = self.broker.expire_all(&self.db) {
+                    error!("[{}] 미체결 주문 취소 실패: {}", now, e);
+                }
+                if self.overview_ready {
+                    self.db.finish_overview()?;
+                }
+                if self.day_started {
+                    self.model.on_end_of_day(&snapshot)?;
+                }
+                if let Some(market_data) = &mut self.market_data {
+                    market_data.clear();
+                }
+                self.overview_ready = false;
+                self.day_started = false;
+            }
+            TimeSignal::Overnight => {
+                info!("[{}] 다음 거래일 대기", now);
+            }
+        }
+        Ok(())
+    }
+
+    /// `now`까지 받은 체결과 호가를 반영하여 스냅샷을 만듭니다.
+    fn snapshot(&mut self, now: DateTime<Local>) -> MarketSnapshot {
+        match &mut self.market_data {
+            Some(market_data) => {
+                market_data.update(now.naive_local());
+                MarketSnapshot::new(now)
+                    .with_bars(market_data.bars())
+                    .with_order_books(market_data.order_books())
+            }
+            None => MarketSnapshot::new(now),
+        }
+    }
+
+    fn start_day(&mut self, snapshot: &MarketSnapshot) -> Result<(), StockrsError> {
+        if !self.day_started {
+            self.model.on_start_of_day(snapshot)?;
+            self.day_started = true;
+        }
+        Ok(())
+    }
+
+    fn open_overview(&mut self) -> Result<(), StockrsError> {
+        if !self.overview_ready {
+            self.db.insert_overview()?;
+            self.overview_ready = true;
+        }
+        Ok(())
+    }
+
+    /// 모델에게 주문을 받아 브로커로 실행합니다.
+    fn trade(&mut self, signal: TimeSignal, snapshot: &MarketSnapshot) -> Result<(), StockrsError> {
+        let reader = self.db.reader();
+        let holdings = match reader.get_holdings() {
+            Ok(holdings) => holdings,
+            Err(e) => {
+                // 보유 종목을 모르면 중복 주문을 낼 수 있으므로 이번 신호는 매매하지 않음
+                error!("[{}] 보유 종목 조회 실패, 매매 건너뜀: {}", snapshot.get_time(), e);
+                return Ok(());
+            }
+        };
+        let orders = self.model.on_signal(signal, snapshot, &holdings, reader)?;
+        for order in &orders {
+            // 주문 하나의 실패로 세션 전체를 멈추지 않음
+            match self.broker.execute(order, &self.db) {
+                Ok(order_id) => info!("[{}] 주문 접수 {} {}", snapshot.get_time(), order.stockcode, order_id),
+                Err(e) => error!("[{}] 주문 실패 {}: {}", snapshot.get_time(), order.stockcode, e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::db_api::DbApi;
+    use crate::broker::make_broker;
+    use crate::data_reader::DbDataReader;
+    use crate::price_db::PriceDB;
+    use crate::time::SimulatedClock;
+    use crate::types::broker::{BrokerType, Order, OrderSide, OrderType};
+    use crate::types::data_reader::{DataReader, DataReaderType};
+    use crate::types::price::Bar;
+    use crate::types::trading::Holding;
+    use chrono::{NaiveDate, TimeZone, Timelike};
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    /// 장 시작에 예수금이 충분하면 10주 매수, 09:05에 현재가보다 500원 높은 지정가로 전량 매도하는 테스트 모델
+    struct BuyThenSell;
+
+    impl Model for BuyThenSell {
+        fn on_signal(&mut self, signal: TimeSignal, snapshot: &MarketSnapshot, holdings: &[Holding], reader: &dyn DataReader) -> Result<Vec<Order>, StockrsError> {
+            let time = snapshot.get_time();
+            let order = |side, price| Order {
+                date: time.naive_local(),
+                stockcode: "005930".to_string(),
+                side,
+                order_type: OrderType::Limit,
+                quantity: 10,
+                price,
+                strategy: "test".to_string(),
+                timeout: None,
+            };
+            match signal {
+                TimeSignal::MarketOpen if reader.get_orderable_cash()? >= 700_000.0 => Ok(vec![order(OrderSide::Buy, 70000.0)]),
+                TimeSignal::Update if time.minute() == 5 && holdings.iter().any(|h| h.get_orderable_quantity() == 10) => {
+                    Ok(vec![order(OrderSide::Sell, reader.get_current_price("005930")? + 500.0)])
+                }
+                _ => Ok(vec![]),
+            }
+        }
+    }
+
+    #[test]
+    fn test_backtest_day() {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap();
+        let prices = PriceDB::new(PathBuf::from(":memory:")).unwrap();
+        for minute in 0..=10 {
+            let time = date.and_hms_opt(9, minute, 0).unwrap();
+            prices.upsert_minute_bar("005930", &Bar::new(time, 70500.0, 71500.0, 69900.0, 70500.0, 1000)).unwrap();
+        }
+
+        let clock = SimulatedClock::new(Local.with_ymd_and_hms(2025, 7, 16, 8, 0, 0).unwrap());
+        let api = Arc::new(DbApi::new(prices, clock.clone(), 1_000_000.0));
+
+        let db_path = std::env::temp_dir().join(format!("stockrs_runner_backtest_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&db_path);
+        let db = DBManager::new(db_path.clone(), DataReaderType::DB(api.clone())).unwrap();
+        let mut runner = Runner::new(TimeService::with_clock(clock.clone()), make_broker(BrokerType::DB(api)), db, Box::new(BuyThenSell));
+        runner.run_until(Local.with_ymd_and_hms(2025, 7, 16, 16, 0, 0).unwrap()).unwrap();
+
+        assert!(runner.db.holdings().unwrap().is_empty());
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        let (open, close): (f64, f64) = conn.query_row(
+            "SELECT open, close FROM overview WHERE date = '2025-07-16'",
+            (),
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).unwrap();
+        assert_eq!(open, 1_000_000.0);
+        // 매매차익 10,000 - 수수료 98 - 수수료 99 - 농어촌특별세 1,065 (2025년 KOSPI 증권거래세 0%)
+        assert_eq!(close, 1_008_738.0);
+        // 체결로 누적한 포지션의 실현손익도 가상 계좌의 자산 변화와 같음
+        assert_eq!(runner.db.position("005930").unwrap().unwrap().get_realized_pnl(), close - open);
+
+        // 거래 기록만으로 계산한 자산도 가상 계좌와 같음
+        let reader = DbDataReader::new(db_path.clone(), 1_000_000.0).unwrap().with_clock(clock);
+        assert_eq!(reader.get_asset_info().unwrap().get_asset(), close);
+
+        drop(conn);
+        let _ = std::fs::remove_file(&db_path);
+    }
+}
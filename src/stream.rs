@@ -0,0 +1,6 @@
+pub mod socket;
+pub mod fill;
+pub mod tick;
+pub mod bar;
+pub mod order_book;
+pub mod market_data;
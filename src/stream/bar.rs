@@ -0,0 +1,132 @@
+use crate::stream::tick::TradeTick;
+use crate::types::price::{Bar, BarInterval};
+use chrono::NaiveDateTime;
+use log::debug;
+use std::collections::HashMap;
+
+/// 실시간 체결을 종목별 분봉으로 모읍니다.
+///
+/// 봉의 시각은 구간 시작 시각이며, 구간이 끝난 봉만 `get_bars`로 조회됩니다.
+/// 체결이 없는 구간의 봉은 만들지 않습니다.
+pub struct BarAggregator {
+    interval: BarInterval,
+    /// 종목별 아직 끝나지 않은 봉
+    building: HashMap<String, Bar>,
+    /// 종목별 완성된 봉 (시간 오름차순)
+    completed: HashMap<String, Vec<Bar>>,
+}
+
+impl BarAggregator {
+    pub fn new(interval: BarInterval) -> Self {
+        Self { interval, building: HashMap::new(), completed: HashMap::new() }
+    }
+
+    pub fn get_interval(&self) -> BarInterval { self.interval }
+
+    /// 체결 한 건을 반영합니다. 다음 구간의 체결이 오면 만들던 봉을 완성합니다.
+    /// 이미 완성된 구간의 체결(지연 도착)은 버립니다.
+    pub fn push(&mut self, tick: &TradeTick) {
+        let start = self.interval.bucket_start(tick.get_time());
+        let code = tick.get_stockcode();
+        let last_completed = self.completed.get(code).and_then(|bars| bars.last()).map(|bar| bar.get_time());
+        if last_completed.is_some_and(|last| start <= last) {
+            debug!("완성된 봉의 체결을 버립니다: {} {}", code, tick.get_time());
+            return;
+        }
+
+        let (price, volume) = (tick.get_price(), tick.get_volume());
+        let bar = match self.building.get(code) {
+            Some(bar) if bar.get_time() == start => Bar::new(
+                start,
+                bar.get_open(),
+                bar.get_high().max(price),
+                bar.get_low().min(price),
+                price,
+                bar.get_volume() + volume,
+            ),
+            Some(bar) if bar.get_time() > start => {
+                debug!("지난 구간의 체결을 버립니다: {} {}", code, tick.get_time());
+                return;
+            }
+            previous => {
+                if let Some(previous) = previous.copied() {
+                    self.completed.entry(code.to_string()).or_default().push(previous);
+                }
+                Bar::new(start, price, price, price, price, volume)
+            }
+        };
+        self.building.insert(code.to_string(), bar);
+    }
+
+    /// `now`까지 구간이 끝난 봉을 모두 완성합니다.
+    pub fn close_until(&mut self, now: NaiveDateTime) {
+        let duration = self.interval.duration();
+        let finished: Vec<String> = self.building.iter()
+            .filter(|(_, bar)| bar.get_time() + duration <= now)
+            .map(|(code, _)| code.clone())
+            .collect();
+        for code in finished {
+            if let Some(bar) = self.building.remove(&code) {
+                self.completed.entry(code).or_default().push(bar);
+            }
+        }
+    }
+
+    /// 완성된 봉 (시간 오름차순)
+    pub fn get_bars(&self, stockcode: &str) -> &[Bar] {
+        self.completed.get(stockcode).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// 종목별 완성된 봉 전체
+    pub fn get_all(&self) -> &HashMap<String, Vec<Bar>> {
+        &self.completed
+    }
+
+    pub fn clear(&mut self) {
+        self.building.clear();
+        self.completed.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_aggregate_minute_bars() {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap();
+        let at = |h, m, s| date.and_hms_opt(h, m, s).unwrap();
+        let tick = |time, price, volume| TradeTick::new("005930".to_string(), time, price, volume);
+        let mut minute = BarAggregator::new(BarInterval::Minute1);
+        let mut five = BarAggregator::new(BarInterval::Minute5);
+        let ticks = [
+            tick(at(9, 0, 1), 70000.0, 10),
+            tick(at(9, 0, 30), 70300.0, 5),
+            tick(at(9, 0, 59), 69900.0, 1),
+            tick(at(9, 1, 10), 70100.0, 2),
+            tick(at(9, 4, 59), 70500.0, 4),
+            tick(at(9, 5, 0), 70400.0, 1),
+        ];
+        for tick in &ticks {
+            minute.push(tick);
+            five.push(tick);
+        }
+
+        // 다음 구간의 체결이 온 봉은 완성, 마지막 봉은 구간이 끝나야 완성
+        assert_eq!(minute.get_bars("005930"), &[
+            Bar::new(at(9, 0, 0), 70000.0, 70300.0, 69900.0, 69900.0, 16),
+            Bar::new(at(9, 1, 0), 70100.0, 70100.0, 70100.0, 70100.0, 2),
+            Bar::new(at(9, 4, 0), 70500.0, 70500.0, 70500.0, 70500.0, 4),
+        ]);
+        assert_eq!(five.get_bars("005930"), &[Bar::new(at(9, 0, 0), 70000.0, 70500.0, 69900.0, 70500.0, 22)]);
+        minute.close_until(at(9, 5, 59));
+        assert_eq!(minute.get_bars("005930").len(), 3);
+        minute.close_until(at(9, 6, 0));
+        assert_eq!(minute.get_bars("005930").len(), 4);
+
+        // 완성된 구간에 늦게 도착한 체결은 버림
+        minute.push(&tick(at(9, 3, 0), 80000.0, 100));
+        assert_eq!(minute.get_bars("005930").len(), 4);
+    }
+}
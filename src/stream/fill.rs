@@ -0,0 +1,244 @@
+use crate::api::session::{session, KisSession};
+use crate::error::{ParseError, StockrsError};
+use crate::stream::socket::{FrameParser, Subscription};
+use crate::types::api::ApiEnv;
+use crate::types::broker::{Fill, OrderSide};
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
+use std::sync::Arc;
+
+// 국내주식 실시간체결통보[실시간-005]
+
+// output ('^' 구분, 복호화 후)
+//  0 CUST_ID 고객 ID
+//  2 ODER_NO 주문번호
+//  3 OODER_NO 원주문번호
+//  4 SELN_BYOV_CLS 매도매수구분	01: 매도, 02: 매수
+//  8 STCK_SHRN_ISCD 주식단축종목코드
+//  9 CNTG_QTY 체결수량
+//  10 CNTG_UNPR 체결단가
+//  11 STCK_CNTG_HOUR 주식체결시간	HHMMSS
+//  12 RFUS_YN 거부여부	0: 정상, 1: 거부
+//  13 CNTG_YN 체결여부	1: 주문·정정·취소·거부 접수, 2: 체결
+//  16 ODER_QTY 주문수량
+
+/// 통보 한 건의 필드 수
+const NOTICE_FIELDS: usize = 23;
+
+fn tr_id(env: ApiEnv) -> &'static str {
+    match env {
+        ApiEnv::Real => "H0STCNI0",
+        ApiEnv::Paper => "H0STCNI9",
+    }
+}
+
+/// 실시간 체결통보 한 건
+#[derive(Debug, Clone)]
+pub struct ExecutionNotice {
+    order_id: String,
+    /// 원주문번호 (정정·취소 통보)
+    original_order_id: String,
+    stockcode: String,
+    side: OrderSide,
+    /// 체결수량
+    quantity: u32,
+    /// 체결단가
+    price: f64,
+    time: NaiveDateTime,
+    /// 체결 통보 여부 (아니면 주문·정정·취소 접수 통보)
+    filled: bool,
+    rejected: bool,
+}
+
+impl ExecutionNotice {
+    /// 복호화한 데이터에서 `count`건의 통보를 읽습니다. 체결시간은 `date`의 시각으로 해석합니다.
+    pub fn parse_all(data: &str, count: usize, date: NaiveDate) -> Result<Vec<Self>, StockrsError> {
+        let fields: Vec<&str> = data.split('^').collect();
+        let width = match count {
+            0 => NOTICE_FIELDS,
+            count => (fields.len() / count).max(NOTICE_FIELDS),
+        };
+        fields.chunks(width)
+            .filter(|record| record.len() >= NOTICE_FIELDS)
+            .map(|record| Self::parse(record, date))
+            .collect()
+    }
+
+    fn parse(record: &[&str], date: NaiveDate) -> Result<Self, StockrsError> {
+        let time = NaiveTime::parse_from_str(record[11].trim(), "%H%M%S")?;
+        let filled = record[13].trim() == "2";
+        let number = |index: usize| record[index].trim().trim_start_matches('0').to_string();
+        Ok(Self {
+            order_id: record[2].trim().to_string(),
+            original_order_id: record[3].trim().to_string(),
+            stockcode: record[8].trim().to_string(),
+            side: match record[4].trim() {
+                "01" => OrderSide::Sell,
+                "02" => OrderSide::Buy,
+                other => return Err(ParseError::MissingField(format!("SELN_BYOV_CLS={}", other)).into()),
+            },
+            quantity: match number(9).as_str() {
+                "" => 0,
+                quantity => quantity.parse()?,
+            },
+            price: match number(10).as_str() {
+                "" => 0.0,
+                price => price.parse()?,
+            },
+            time: date.and_time(time),
+            filled,
+            rejected: record[12].trim() == "1",
+        })
+    }
+
+    pub fn get_order_id(&self) -> &str { &self.order_id }
+    pub fn get_original_order_id(&self) -> &str { &self.original_order_id }
+    pub fn get_stockcode(&self) -> &str { &self.stockcode }
+    pub fn get_side(&self) -> OrderSide { self.side }
+    pub fn get_quantity(&self) -> u32 { self.quantity }
+    pub fn get_price(&self) -> f64 { self.price }
+    pub fn get_time(&self) -> NaiveDateTime { self.time }
+    pub fn is_filled(&self) -> bool { self.filled }
+    pub fn is_rejected(&self) -> bool { self.rejected }
+
+    /// 체결 통보면 주문 추적에 넘길 체결 정보를 반환합니다.
+    pub fn to_fill(&self) -> Option<Fill> {
+        (self.filled && !self.rejected && self.quantity > 0).then(|| Fill {
+            order_id: self.order_id.clone(),
+            quantity: self.quantity,
+            price: self.price,
+            time: self.time,
+        })
+    }
+}
+
+/// 체결통보 구독
+///
+/// HTS ID로 체결통보를 구독하고, 복호화한 통보를 쌓아 둡니다.
+/// 브로커는 호출될 때마다 `drain`으로 쌓인 통보를 꺼내 주문 추적에 반영하므로,
+/// 체결 조회 결과를 기다리지 않고 통보받은 체결 시각·가격으로 거래 내역이 기록됩니다.
+pub struct FillStream {
+    subscription: Subscription<ExecutionNotice>,
+}
+
+impl FillStream {
+    /// `install`로 등록된 `env` 세션으로 구독을 시작합니다.
+    pub fn start(env: ApiEnv) -> Result<Self, StockrsError> {
+        Self::with_session(session(env)?)
+    }
+
+    pub fn with_session(session: Arc<KisSession>) -> Result<Self, StockrsError> {
+        let hts_id = session.get_config().hts_id.clone()
+            .ok_or_else(|| StockrsError::Config("체결통보 구독에 필요한 hts_id가 없습니다".to_string()))?;
+        let tr_id = tr_id(session.get_env());
+        let parser: FrameParser<ExecutionNotice> = Box::new(move |id, data, count| match id == tr_id {
+            true => ExecutionNotice::parse_all(data, count, Local::now().date_naive()),
+            false => Ok(Vec::new()),
+        });
+        let subscription = Subscription::spawn(session, vec![(tr_id.to_string(), hts_id)], parser);
+        Ok(Self { subscription })
+    }
+
+    /// 지금까지 받은 통보를 모두 꺼냅니다.
+    pub fn drain(&self) -> Vec<ExecutionNotice> {
+        self.subscription.drain()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_kis::{MockFixtures, MockKisServer};
+    use aes::cipher::{block_padding::Pkcs7, BlockEncryptMut, KeyIvInit};
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde_json::{json, Value};
+    use std::net::TcpListener;
+    use std::time::Duration;
+    use tungstenite::Message;
+
+    const KEY: &str = "0123456789abcdef0123456789abcdef";
+    const IV: &str = "fedcba9876543210";
+
+    fn notice(order_id: &str, filled: bool) -> String {
+        let mut fields = vec![""; NOTICE_FIELDS];
+        fields[0] = "hts";
+        fields[2] = order_id;
+        fields[4] = "02";
+        fields[8] = "005930";
+        fields[9] = "0000000003";
+        fields[10] = "000070100";
+        fields[11] = "090512";
+        fields[12] = "0";
+        fields[13] = if filled { "2" } else { "1" };
+        fields[16] = "0000000010";
+        fields.join("^")
+    }
+
+    fn encrypt(plain: &str) -> String {
+        let data = cbc::Encryptor::<aes::Aes256>::new_from_slices(KEY.as_bytes(), IV.as_bytes())
+            .unwrap()
+            .encrypt_padded_vec_mut::<Pkcs7>(plain.as_bytes());
+        STANDARD.encode(data)
+    }
+
+    #[test]
+    fn test_parse_notices() {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap();
+        let data = format!("{}^{}", notice("0000001", false), notice("0000001", true));
+        let notices = ExecutionNotice::parse_all(&data, 2, date).unwrap();
+        assert_eq!(notices.len(), 2);
+        assert!(notices[0].to_fill().is_none());
+
+        let fill = notices[1].to_fill().unwrap();
+        assert_eq!((fill.order_id.as_str(), fill.quantity, fill.price), ("0000001", 3, 70100.0));
+        assert_eq!(fill.time, date.and_hms_opt(9, 5, 12).unwrap());
+    }
+
+    #[test]
+    fn test_stream_decrypts_notices() {
+        // 접속키는 모의 REST 서버에서, 체결통보는 테스트 웹소켓 서버에서 받음
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let ws_url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            let request: Value = serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap();
+            assert_eq!(request["body"]["input"]["tr_id"], "H0STCNI9");
+            assert_eq!(request["body"]["input"]["tr_key"], "hts");
+
+            let subscribed = json!({
+                "header": { "tr_id": "H0STCNI9", "tr_key": "hts", "encrypt": "N" },
+                "body": { "rt_cd": "0", "msg_cd": "OPSP0000", "msg1": "SUBSCRIBE SUCCESS", "output": { "iv": IV, "key": KEY } },
+            });
+            socket.send(Message::Text(subscribed.to_string())).unwrap();
+            let ping = json!({ "header": { "tr_id": "PINGPONG", "datetime": "20250716090500" } }).to_string();
+            socket.send(Message::Text(ping.clone())).unwrap();
+            assert_eq!(socket.read().unwrap().to_text().unwrap(), ping);
+            // 복호화할 수 없는 프레임은 건너뛰고 같은 연결로 다음 통보를 받음
+            socket.send(Message::Text("1|H0STCNI9|001|not-encrypted".to_string())).unwrap();
+            socket.send(Message::Text(format!("1|H0STCNI9|001|{}", encrypt(&notice("0000001", true))))).unwrap();
+            // 구독 해제 요청 또는 연결 종료까지 대기
+            let _ = socket.read();
+        });
+
+        let rest = MockKisServer::start(MockFixtures::default()).unwrap();
+        let mut config = rest.get_config();
+        config.ws_url = Some(ws_url);
+        config.hts_id = Some("hts".to_string());
+        let stream = FillStream::with_session(Arc::new(KisSession::new(ApiEnv::Paper, config))).unwrap();
+
+        let mut notices = Vec::new();
+        for _ in 0..50 {
+            notices.extend(stream.drain());
+            if !notices.is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].to_fill().unwrap().quantity, 3);
+
+        drop(stream);
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+use crate::stream::bar::BarAggregator;
+use crate::stream::order_book::OrderBookStream;
+use crate::stream::tick::{TickStream, TradeTick};
+use crate::types::order_book::OrderBook;
+use crate::types::price::{Bar, BarInterval};
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+
+/// 종목별·주기별 완성된 분봉
+pub type BarSet = HashMap<BarInterval, HashMap<String, Vec<Bar>>>;
+
+/// 실시간 체결가를 1분봉/5분봉으로 모아 모델에 제공합니다.
+///
+/// `Runner`가 장 시작과 매 업데이트마다 `update`를 호출하므로, 모델은 그 시각까지 끝난 봉을
+/// `MarketSnapshot::get_bars`로 받습니다. 업데이트가 1분 단위이므로 1분봉은 매 업데이트,
+/// 5분봉은 5분마다 새 봉이 추가됩니다.
+///
+/// 호가 구독(`with_order_books`)을 연결하면 종목별 최신 호가도 함께 제공합니다.
+pub struct MarketData {
+    stream: Option<TickStream>,
+    aggregators: Vec<BarAggregator>,
+    book_stream: Option<OrderBookStream>,
+    /// 종목별 최신 호가
+    order_books: HashMap<String, OrderBook>,
+}
+
+impl MarketData {
+    pub fn new(stream: TickStream) -> Self {
+        Self { stream: Some(stream), ..Self::offline() }
+    }
+
+    /// 구독 없이 `push`로 체결을 넣어 사용합니다. (시험·재생용)
+    pub fn offline() -> Self {
+        Self {
+            stream: None,
+            aggregators: vec![BarAggregator::new(BarInterval::Minute1), BarAggregator::new(BarInterval::Minute5)],
+            book_stream: None,
+            order_books: HashMap::new(),
+        }
+    }
+
+    /// 실시간 호가 구독을 연결합니다.
+    pub fn with_order_books(mut self, stream: OrderBookStream) -> Self {
+        self.book_stream = Some(stream);
+        self
+    }
+
+    pub fn push(&mut self, tick: &TradeTick) {
+        for aggregator in &mut self.aggregators {
+            aggregator.push(tick);
+        }
+    }
+
+    /// 호가를 반영합니다. 이미 더 최근 호가가 있으면 버립니다.
+    pub fn push_order_book(&mut self, book: OrderBook) {
+        match self.order_books.get(book.get_stockcode()) {
+            Some(latest) if latest.get_time() > book.get_time() => {}
+            _ => {
+                self.order_books.insert(book.get_stockcode().to_string(), book);
+            }
+        }
+    }
+
+    /// 받은 체결과 호가를 반영하고 `now`까지 구간이 끝난 봉을 완성합니다.
+    pub fn update(&mut self, now: NaiveDateTime) {
+        let ticks = self.stream.as_ref().map(TickStream::drain).unwrap_or_default();
+        for tick in &ticks {
+            self.push(tick);
+        }
+        let books = self.book_stream.as_ref().map(OrderBookStream::drain).unwrap_or_default();
+        for book in books {
+            self.push_order_book(book);
+        }
+        for aggregator in &mut self.aggregators {
+            aggregator.close_until(now);
+        }
+    }
+
+    pub fn get_bars(&self, stockcode: &str, interval: BarInterval) -> &[Bar] {
+        self.aggregators.iter()
+            .find(|aggregator| aggregator.get_interval() == interval)
+            .map(|aggregator| aggregator.get_bars(stockcode))
+            .unwrap_or_default()
+    }
+
+    /// 완성된 봉 전체의 복사본
+    pub fn bars(&self) -> BarSet {
+        self.aggregators.iter()
+            .map(|aggregator| (aggregator.get_interval(), aggregator.get_all().clone()))
+            .collect()
+    }
+
+    pub fn get_order_book(&self, stockcode: &str) -> Option<&OrderBook> {
+        self.order_books.get(stockcode)
+    }
+
+    /// 종목별 최신 호가의 복사본
+    pub fn order_books(&self) -> HashMap<String, OrderBook> {
+        self.order_books.clone()
+    }
+
+    /// 하루치 봉과 호가를 비웁니다. (장 종료 후)
+    pub fn clear(&mut self) {
+        for aggregator in &mut self.aggregators {
+            aggregator.clear();
+        }
+        self.order_books.clear();
+    }
+}
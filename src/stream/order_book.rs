@@ -0,0 +1,101 @@
+use crate::api::session::{session, KisSession};
+use crate::error::StockrsError;
+use crate::stream::socket::{FrameParser, Subscription};
+use crate::types::api::ApiEnv;
+use crate::types::order_book::{BookLevel, OrderBook};
+use chrono::{Local, NaiveDate, NaiveTime};
+use std::sync::Arc;
+
+/// 국내주식 실시간호가 TR (실전/모의 공통)
+const TR_ID: &str = "H0STASP0";
+/// 한 연결에서 구독할 수 있는 최대 건수
+const MAX_SUBSCRIPTIONS: usize = 41;
+/// 호가 단계 수
+const LEVELS: usize = 10;
+
+// 국내주식 실시간호가 (KRX)[실시간-004]
+
+// output ('^' 구분)
+//  0 MKSC_SHRN_ISCD 유가증권 단축 종목코드
+//  1 BSOP_HOUR 영업 시간	HHMMSS
+//  3~12 ASKP1~10 매도호가
+//  13~22 BIDP1~10 매수호가
+//  23~32 ASKP_RSQN1~10 매도호가 잔량
+//  33~42 BIDP_RSQN1~10 매수호가 잔량
+
+/// 호가 한 건에서 사용하는 필드 수
+const BOOK_FIELDS: usize = 43;
+
+/// 실시간 호가 데이터에서 `count`건의 호가를 읽습니다. 시각은 `date`의 시각으로 해석합니다.
+pub fn parse_order_books(data: &str, count: usize, date: NaiveDate) -> Result<Vec<OrderBook>, StockrsError> {
+    let fields: Vec<&str> = data.split('^').collect();
+    let width = match count {
+        0 => BOOK_FIELDS,
+        count => (fields.len() / count).max(BOOK_FIELDS),
+    };
+    fields.chunks(width)
+        .filter(|record| record.len() >= BOOK_FIELDS)
+        .map(|record| {
+            let time = NaiveTime::parse_from_str(record[1].trim(), "%H%M%S")?;
+            let levels = |price_at: usize, quantity_at: usize| -> Result<Vec<BookLevel>, StockrsError> {
+                (0..LEVELS)
+                    .map(|i| Ok(BookLevel { price: record[price_at + i].trim().parse()?, quantity: record[quantity_at + i].trim().parse()? }))
+                    .collect()
+            };
+            Ok(OrderBook::new(record[0].trim().to_string(), date.and_time(time), levels(3, 23)?, levels(13, 33)?))
+        })
+        .collect()
+}
+
+/// 종목별 실시간 호가 구독
+pub struct OrderBookStream {
+    subscription: Subscription<OrderBook>,
+}
+
+impl OrderBookStream {
+    /// `install`로 등록된 `env` 세션으로 `stockcodes`의 호가 구독을 시작합니다.
+    pub fn start(env: ApiEnv, stockcodes: &[String]) -> Result<Self, StockrsError> {
+        Self::with_session(session(env)?, stockcodes)
+    }
+
+    pub fn with_session(session: Arc<KisSession>, stockcodes: &[String]) -> Result<Self, StockrsError> {
+        if stockcodes.len() > MAX_SUBSCRIPTIONS {
+            return Err(StockrsError::Config(format!("실시간 호가는 최대 {}종목까지 구독할 수 있습니다", MAX_SUBSCRIPTIONS)));
+        }
+        let requests = stockcodes.iter().map(|code| (TR_ID.to_string(), code.clone())).collect();
+        let parser: FrameParser<OrderBook> = Box::new(|tr_id, data, count| match tr_id == TR_ID {
+            true => parse_order_books(data, count, Local::now().date_naive()),
+            false => Ok(Vec::new()),
+        });
+        Ok(Self { subscription: Subscription::spawn(session, requests, parser) })
+    }
+
+    /// 지금까지 받은 호가를 모두 꺼냅니다.
+    pub fn drain(&self) -> Vec<OrderBook> {
+        self.subscription.drain()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_order_book() {
+        let mut fields: Vec<String> = vec!["0".to_string(); 59];
+        fields[0] = "005930".to_string();
+        fields[1] = "090001".to_string();
+        for i in 0..LEVELS {
+            fields[3 + i] = format!("{}", 70100 + 100 * i);
+            fields[13 + i] = format!("{}", 70000 - 100 * i as i64);
+            fields[23 + i] = "10".to_string();
+            fields[33 + i] = "20".to_string();
+        }
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap();
+        let books = parse_order_books(&fields.join("^"), 1, date).unwrap();
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].get_time(), date.and_hms_opt(9, 0, 1).unwrap());
+        assert_eq!(books[0].spread(), Some(100.0));
+        assert_eq!(books[0].depth(crate::types::broker::OrderSide::Buy, 10), 200);
+    }
+}
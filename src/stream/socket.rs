@@ -0,0 +1,283 @@
+use crate::api::session::KisSession;
+use crate::error::{ApiError, ParseError, StockrsError};
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use log::{debug, warn};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+type Aes256CbcDec = cbc::Decryptor<aes::Aes256>;
+
+/// 프레임을 기다리는 최대 시간 (이 시간마다 종료 요청을 확인할 수 있도록 반환)
+const READ_TIMEOUT: Duration = Duration::from_millis(500);
+/// 연결이 끊긴 뒤 다시 접속하기까지의 대기 시간
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// 실시간 웹소켓에서 받은 메시지
+#[derive(Debug, Clone)]
+pub enum Frame {
+    /// 구독 응답 등 JSON 제어 메시지
+    Control(Value),
+    /// `암호화여부|TR_ID|데이터건수|데이터` 형식의 실시간 데이터
+    Data {
+        tr_id: String,
+        encrypted: bool,
+        /// 데이터에 담긴 레코드 수
+        count: usize,
+        payload: String,
+    },
+}
+
+impl Frame {
+    /// 텍스트 메시지를 프레임으로 변환합니다. 형식이 맞지 않으면 `None`입니다.
+    pub fn parse(text: &str) -> Option<Frame> {
+        if text.starts_with('{') {
+            return serde_json::from_str(text).ok().map(Frame::Control);
+        }
+        let mut parts = text.splitn(4, '|');
+        let encrypted = parts.next()? == "1";
+        let tr_id = parts.next()?.to_string();
+        let count = parts.next()?.parse().ok()?;
+        let payload = parts.next()?.to_string();
+        Some(Frame::Data { tr_id, encrypted, count, payload })
+    }
+}
+
+/// 구독 응답으로 받은 AES-256-CBC 키와 IV
+#[derive(Debug, Clone)]
+pub struct Cipher {
+    key: String,
+    iv: String,
+}
+
+impl Cipher {
+    pub fn new(key: String, iv: String) -> Self {
+        Self { key, iv }
+    }
+
+    /// Base64로 인코딩된 암호문을 복호화합니다.
+    pub fn decrypt(&self, payload: &str) -> Result<String, StockrsError> {
+        let data = STANDARD.decode(payload).map_err(|e| ParseError::Decrypt(e.to_string()))?;
+        let plain = Aes256CbcDec::new_from_slices(self.key.as_bytes(), self.iv.as_bytes())
+            .map_err(|e| ParseError::Decrypt(e.to_string()))?
+            .decrypt_padded_vec_mut::<Pkcs7>(&data)
+            .map_err(|e| ParseError::Decrypt(e.to_string()))?;
+        Ok(String::from_utf8(plain).map_err(|e| ParseError::Decrypt(e.to_string()))?)
+    }
+}
+
+/// 구독 응답을 확인합니다. 실패 응답이면 오류, 암호화 키가 있으면 `Cipher`를 반환합니다.
+pub fn subscription_result(control: &Value) -> Result<Option<Cipher>, StockrsError> {
+    let body = &control["body"];
+    if body.is_null() {
+        return Ok(None);
+    }
+    if body["rt_cd"].as_str() != Some("0") {
+        return Err(ApiError::Response {
+            code: body["msg_cd"].as_str().unwrap_or_default().to_string(),
+            message: body["msg1"].as_str().unwrap_or_default().to_string(),
+        }.into());
+    }
+    let output = &body["output"];
+    Ok(match (output["key"].as_str(), output["iv"].as_str()) {
+        (Some(key), Some(iv)) => Some(Cipher::new(key.to_string(), iv.to_string())),
+        _ => None,
+    })
+}
+
+fn network(e: tungstenite::Error) -> StockrsError {
+    ApiError::Network(e.to_string()).into()
+}
+
+/// KIS 실시간 웹소켓 연결
+///
+/// 하나의 연결에서 여러 TR을 구독할 수 있으며, 서버가 보내는 `PINGPONG`은 `next_frame`이 그대로 돌려보냅니다.
+pub struct KisSocket {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+    approval_key: String,
+}
+
+impl KisSocket {
+    /// 세션의 웹소켓 접속키로 실시간 서버에 접속합니다.
+    pub fn connect(session: &KisSession) -> Result<Self, StockrsError> {
+        let approval_key = session.approval_key()?;
+        let (socket, _) = tungstenite::connect(session.get_ws_url()).map_err(network)?;
+        if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+            stream.set_read_timeout(Some(READ_TIMEOUT)).map_err(|e| ApiError::Network(e.to_string()))?;
+        }
+        Ok(Self { socket, approval_key })
+    }
+
+    pub fn subscribe(&mut self, tr_id: &str, tr_key: &str) -> Result<(), StockrsError> {
+        self.request("1", tr_id, tr_key)
+    }
+
+    pub fn unsubscribe(&mut self, tr_id: &str, tr_key: &str) -> Result<(), StockrsError> {
+        self.request("2", tr_id, tr_key)
+    }
+
+    /// 등록(`tr_type` = 1) 또는 해제(2) 요청
+    fn request(&mut self, tr_type: &str, tr_id: &str, tr_key: &str) -> Result<(), StockrsError> {
+        let message = json!({
+            "header": {
+                "approval_key": self.approval_key,
+                "custtype": "P",
+                "tr_type": tr_type,
+                "content-type": "utf-8",
+            },
+            "body": { "input": { "tr_id": tr_id, "tr_key": tr_key } },
+        });
+        self.socket.send(Message::Text(message.to_string())).map_err(network)
+    }
+
+    /// 다음 프레임을 읽습니다. `READ_TIMEOUT` 안에 받은 프레임이 없으면 `None`입니다.
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, StockrsError> {
+        let message = match self.socket.read() {
+            Ok(message) => message,
+            Err(tungstenite::Error::Io(e)) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => return Ok(None),
+            Err(e) => return Err(network(e)),
+        };
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => return Err(ApiError::Network("실시간 서버가 연결을 종료했습니다".to_string()).into()),
+            _ => return Ok(None),
+        };
+        let frame = Frame::parse(&text);
+        if let Some(Frame::Control(control)) = &frame && control["header"]["tr_id"] == "PINGPONG" {
+            self.socket.send(Message::Text(text)).map_err(network)?;
+            return Ok(None);
+        }
+        Ok(frame)
+    }
+}
+
+/// 실시간 데이터 한 프레임을 항목으로 변환하는 함수 (`tr_id`, 복호화한 데이터, 레코드 수)
+pub type FrameParser<T> = Box<dyn FnMut(&str, &str, usize) -> Result<Vec<T>, StockrsError> + Send>;
+
+/// 실시간 구독 스레드
+///
+/// 백그라운드 스레드가 접속 후 `requests`의 (`tr_id`, `tr_key`)를 모두 구독하고,
+/// 받은 데이터를 `parser`로 변환해 채널에 쌓습니다. 암호화된 TR은 구독 응답의 키로 복호화합니다.
+/// 연결이 끊기면 `RECONNECT_DELAY` 후 다시 접속하며, 값이 삭제되면 구독을 해제하고 스레드를 종료합니다.
+pub struct Subscription<T> {
+    receiver: Receiver<T>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> Subscription<T> {
+    pub fn spawn(session: Arc<KisSession>, requests: Vec<(String, String)>, mut parser: FrameParser<T>) -> Self {
+        let (sender, receiver) = channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::SeqCst) {
+                    match listen(&session, &requests, &mut parser, &sender, &stop) {
+                        Ok(()) => break,
+                        Err(e) => {
+                            warn!("실시간 연결 오류, {}초 후 재접속: {}", RECONNECT_DELAY.as_secs(), e);
+                            sleep_unless_stopped(RECONNECT_DELAY, &stop);
+                        }
+                    }
+                }
+            })
+        };
+        Self { receiver, stop, handle: Some(handle) }
+    }
+
+    /// 지금까지 받은 항목을 모두 꺼냅니다.
+    pub fn drain(&self) -> Vec<T> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn sleep_unless_stopped(duration: Duration, stop: &AtomicBool) {
+    let step = Duration::from_millis(100);
+    let mut slept = Duration::ZERO;
+    while slept < duration && !stop.load(Ordering::SeqCst) {
+        std::thread::sleep(step);
+        slept += step;
+    }
+}
+
+/// 접속·구독 후 종료 요청이나 수신 측 해제까지 데이터를 전달합니다.
+///
+/// 종목 하나의 구독 실패나 프레임 하나의 변환 실패는 기록만 하고 연결을 유지합니다.
+fn listen<T>(session: &KisSession, requests: &[(String, String)], parser: &mut FrameParser<T>, sender: &Sender<T>, stop: &AtomicBool) -> Result<(), StockrsError> {
+    let mut socket = KisSocket::connect(session)?;
+    for (tr_id, tr_key) in requests {
+        socket.subscribe(tr_id, tr_key)?;
+    }
+    let mut ciphers: HashMap<String, Cipher> = HashMap::new();
+
+    while !stop.load(Ordering::SeqCst) {
+        match socket.next_frame()? {
+            Some(Frame::Control(control)) => {
+                let header = &control["header"];
+                let tr_id = header["tr_id"].as_str().unwrap_or_default().to_string();
+                match subscription_result(&control) {
+                    Ok(cipher) => {
+                        debug!("실시간 응답 {} {}: {}", tr_id, header["tr_key"], control["body"]["msg1"]);
+                        if let Some(cipher) = cipher {
+                            ciphers.insert(tr_id, cipher);
+                        }
+                    }
+                    Err(e) => warn!("실시간 구독 실패 {} {}: {}", tr_id, header["tr_key"], e),
+                }
+            }
+            Some(Frame::Data { tr_id, encrypted, count, payload }) => {
+                let data = match (encrypted, ciphers.get(&tr_id)) {
+                    (false, _) => payload,
+                    (true, Some(cipher)) => match cipher.decrypt(&payload) {
+                        Ok(data) => data,
+                        Err(e) => {
+                            warn!("실시간 데이터 복호화 실패 {}: {}", tr_id, e);
+                            continue;
+                        }
+                    },
+                    (true, None) => {
+                        warn!("복호화 키를 받기 전의 {} 데이터를 건너뜁니다", tr_id);
+                        continue;
+                    }
+                };
+                let items = match parser(&tr_id, &data, count) {
+                    Ok(items) => items,
+                    Err(e) => {
+                        warn!("실시간 데이터 변환 실패 {}: {}", tr_id, e);
+                        continue;
+                    }
+                };
+                for item in items {
+                    if sender.send(item).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+    for (tr_id, tr_key) in requests {
+        let _ = socket.unsubscribe(tr_id, tr_key);
+    }
+    Ok(())
+}
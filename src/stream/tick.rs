@@ -0,0 +1,111 @@
+use crate::api::session::{session, KisSession};
+use crate::error::StockrsError;
+use crate::stream::socket::{FrameParser, Subscription};
+use crate::types::api::ApiEnv;
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
+use std::sync::Arc;
+
+/// 국내주식 실시간체결가 TR (실전/모의 공통)
+const TR_ID: &str = "H0STCNT0";
+/// 한 연결에서 구독할 수 있는 최대 건수
+const MAX_SUBSCRIPTIONS: usize = 41;
+
+// 국내주식 실시간체결가 (KRX)[실시간-003]
+
+// output ('^' 구분)
+//  0 MKSC_SHRN_ISCD 유가증권 단축 종목코드
+//  1 STCK_CNTG_HOUR 주식 체결 시간	HHMMSS
+//  2 STCK_PRPR 주식 현재가
+//  12 CNTG_VOL 체결 거래량
+
+/// 체결 한 건의 필드 수
+const TICK_FIELDS: usize = 46;
+
+/// 실시간 체결 한 건
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeTick {
+    stockcode: String,
+    time: NaiveDateTime,
+    price: f64,
+    volume: u64,
+}
+
+impl TradeTick {
+    pub fn new(stockcode: String, time: NaiveDateTime, price: f64, volume: u64) -> Self {
+        Self { stockcode, time, price, volume }
+    }
+
+    /// 실시간 데이터에서 `count`건의 체결을 읽습니다. 체결시간은 `date`의 시각으로 해석합니다.
+    pub fn parse_all(data: &str, count: usize, date: NaiveDate) -> Result<Vec<Self>, StockrsError> {
+        let fields: Vec<&str> = data.split('^').collect();
+        let width = match count {
+            0 => TICK_FIELDS,
+            count => (fields.len() / count).max(TICK_FIELDS),
+        };
+        fields.chunks(width)
+            .filter(|record| record.len() >= TICK_FIELDS)
+            .map(|record| {
+                let time = NaiveTime::parse_from_str(record[1].trim(), "%H%M%S")?;
+                Ok(Self::new(record[0].trim().to_string(), date.and_time(time), record[2].trim().parse()?, record[12].trim().parse()?))
+            })
+            .collect()
+    }
+
+    pub fn get_stockcode(&self) -> &str { &self.stockcode }
+    pub fn get_time(&self) -> NaiveDateTime { self.time }
+    pub fn get_price(&self) -> f64 { self.price }
+    pub fn get_volume(&self) -> u64 { self.volume }
+}
+
+/// 종목별 실시간 체결가 구독
+pub struct TickStream {
+    subscription: Subscription<TradeTick>,
+}
+
+impl TickStream {
+    /// `install`로 등록된 `env` 세션으로 `stockcodes`의 체결가 구독을 시작합니다.
+    pub fn start(env: ApiEnv, stockcodes: &[String]) -> Result<Self, StockrsError> {
+        Self::with_session(session(env)?, stockcodes)
+    }
+
+    pub fn with_session(session: Arc<KisSession>, stockcodes: &[String]) -> Result<Self, StockrsError> {
+        if stockcodes.len() > MAX_SUBSCRIPTIONS {
+            return Err(StockrsError::Config(format!("실시간 체결가는 최대 {}종목까지 구독할 수 있습니다", MAX_SUBSCRIPTIONS)));
+        }
+        let requests = stockcodes.iter().map(|code| (TR_ID.to_string(), code.clone())).collect();
+        let parser: FrameParser<TradeTick> = Box::new(|tr_id, data, count| match tr_id == TR_ID {
+            true => TradeTick::parse_all(data, count, Local::now().date_naive()),
+            false => Ok(Vec::new()),
+        });
+        Ok(Self { subscription: Subscription::spawn(session, requests, parser) })
+    }
+
+    /// 지금까지 받은 체결을 모두 꺼냅니다.
+    pub fn drain(&self) -> Vec<TradeTick> {
+        self.subscription.drain()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ticks() {
+        let record = |code: &'static str, time: &'static str, price: &'static str, volume: &'static str| {
+            let mut fields = vec!["0"; TICK_FIELDS];
+            fields[0] = code;
+            fields[1] = time;
+            fields[2] = price;
+            fields[12] = volume;
+            fields.join("^")
+        };
+        let data = format!("{}^{}", record("005930", "090001", "70100", "15"), record("005930", "090002", "70200", "3"));
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap();
+        let ticks = TradeTick::parse_all(&data, 2, date).unwrap();
+        assert_eq!(ticks, vec![
+            TradeTick::new("005930".to_string(), date.and_hms_opt(9, 0, 1).unwrap(), 70100.0, 15),
+            TradeTick::new("005930".to_string(), date.and_hms_opt(9, 0, 2).unwrap(), 70200.0, 3),
+        ]);
+    }
+}
@@ -2,6 +2,8 @@ use chrono::{Local, DateTime, Duration, Datelike, Weekday, NaiveDate, TimeZone};
 use std::thread;
 use std::fs;
 use std::path::Path;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
 use crate::local_time;
 
 /// Signals corresponding to specific time events within the trading day
@@ -19,21 +21,97 @@ pub enum TimeSignal {
     Overnight,
 }
 
+/// `TimeService`가 현재 시각을 읽고 대기할 때 사용하는 시계
+pub trait Clock {
+    /// 현재 시각을 반환합니다.
+    fn now(&self) -> DateTime<Local>;
+    /// 목표 시각(`target`)까지 대기합니다.
+    fn sleep_until(&self, target: DateTime<Local>);
+}
+
+/// 실제 시스템 시각을 사용하는 시계 (실거래/모의투자용)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WallClock;
+
+impl Clock for WallClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+
+    fn sleep_until(&self, target: DateTime<Local>) {
+        let now = Local::now();
+        if target > now && let Ok(dur) = target.signed_duration_since(now).to_std() {
+            thread::sleep(dur);
+        }
+    }
+}
+
+/// 대기 없이 목표 시각으로 즉시 이동하는 가상 시계 (백테스트/테스트용)
+///
+/// 복제본끼리 같은 시각을 공유하므로 브로커나 데이터 리더에 넘겨
+/// 시뮬레이션 시각을 함께 참조할 수 있습니다.
+#[derive(Debug, Clone)]
+pub struct SimulatedClock {
+    current: Arc<Mutex<DateTime<Local>>>,
+}
+
+impl SimulatedClock {
+    pub fn new(start: DateTime<Local>) -> Self {
+        Self { current: Arc::new(Mutex::new(start)) }
+    }
+
+    /// 가상 시각을 임의의 시각으로 설정합니다.
+    pub fn set(&self, time: DateTime<Local>) {
+        *self.current.lock().unwrap() = time;
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime<Local> {
+        *self.current.lock().unwrap()
+    }
+
+    fn sleep_until(&self, target: DateTime<Local>) {
+        let mut current = self.current.lock().unwrap();
+        if target > *current {
+            *current = target;
+        }
+    }
+}
+
 /// `TimeService` 구조체는 내부에 현재 시간(`current`)을 보관하며,
 /// 다음 이벤트 시각 계산과 대기를 수행합니다.
-pub struct TimeService {
+///
+/// 시각 조회와 대기는 `Clock`에 위임하므로 `SimulatedClock`을 쓰면
+/// 실제 시간 대기 없이 거래일 흐름을 재생할 수 있습니다.
+pub struct TimeService<C: Clock = WallClock> {
+    clock: C,
     current: DateTime<Local>,
     current_signal: TimeSignal,
 }
 
-impl TimeService {
-    /// 새로운 `TimeService` 인스턴스를 생성합니다.
+impl TimeService<WallClock> {
+    /// 시스템 시각을 사용하는 `TimeService` 인스턴스를 생성합니다.
+    pub fn new() -> Self {
+        Self::with_clock(WallClock)
+    }
+}
+
+impl Default for TimeService<WallClock> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> TimeService<C> {
+    /// 주어진 시계를 사용하는 `TimeService` 인스턴스를 생성합니다.
     ///
-    /// 먼저 `Local::now()`로 현재 시각을 가져와 `current`를 설정한 뒤,
+    /// 먼저 `clock.now()`로 현재 시각을 가져와 `current`를 설정한 뒤,
     /// 다음 거래 이벤트를 계산하여 `current`와 `current_signal`을 갱신합니다.
-    pub fn new() -> Self {
-        let now = Local::now();
-        let mut service = TimeService { 
+    pub fn with_clock(clock: C) -> Self {
+        let now = clock.now();
+        let mut service = TimeService {
+            clock,
             current: now,
             current_signal: TimeSignal::DataPrep, // 임시 초기값
         };
@@ -61,14 +139,14 @@ impl TimeService {
         (next_time, signal)
     }
 
+    /// 사용 중인 시계를 반환합니다.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
     /// 주어진 목표 시각(`target`)까지 블로킹 대기를 수행합니다.
     pub fn wait_until(&self, target: DateTime<Local>) {
-        let now = Local::now();
-        if target > now {
-            if let Ok(dur) = target.signed_duration_since(now).to_std() {
-                thread::sleep(dur);
-            }
-        }
+        self.clock.sleep_until(target);
     }
 
     /// 현재 시각(`current`)을 기준으로 다음 이벤트 시각과 해당 시그널을 계산
@@ -82,6 +160,12 @@ impl TimeService {
     fn compute_next_time(&self) -> (DateTime<Local>, TimeSignal) {
         let today = self.current.date_naive();
 
+        // 주말이나 휴장일에는 당일 이벤트 없이 다음 거래일로 이동
+        if is_weekend(today) || is_holiday(today) {
+            let next_date = next_trading_day(today);
+            return (local_time!(next_date, 8, 30, 0), TimeSignal::Overnight);
+        }
+
         let prep_time = local_time!(today, 8, 30, 0);
         let open_time  = local_time!(today, 9, 0, 0);
         let last_upd   = local_time!(today, 15, 29, 0);
@@ -141,9 +225,15 @@ fn load_holidays(year: i32) -> Vec<NaiveDate> {
 }
 
 /// 주어진 날짜가 공휴일인지 확인
+///
+/// 연도별 휴장일 목록은 처음 조회할 때 한 번만 파일에서 읽어 캐시합니다.
 fn is_holiday(date: NaiveDate) -> bool {
-    let holidays = load_holidays(date.year());
-    holidays.contains(&date)
+    static CACHE: OnceLock<Mutex<HashMap<i32, Vec<NaiveDate>>>> = OnceLock::new();
+    let mut cache = CACHE.get_or_init(|| Mutex::new(HashMap::new())).lock().unwrap();
+    cache
+        .entry(date.year())
+        .or_insert_with(|| load_holidays(date.year()))
+        .contains(&date)
 }
 
 /// 다음 영업일(Date 부분) 계산 (주말과 공휴일 건너뛰기)
@@ -169,7 +259,7 @@ mod tests {
         
         // 07:30 -> 데이터 준비
         let now = c.with_ymd_and_hms(2025, 7, 16, 7, 30, 0).unwrap();
-        let service = TimeService { current: now, current_signal: TimeSignal::DataPrep };
+        let service = TimeService { clock: WallClock, current: now, current_signal: TimeSignal::DataPrep };
         let (next, sig) = service.compute_next_time();
         assert_eq!(sig, TimeSignal::DataPrep);
         assert_eq!(next.time().hour(), 8);
@@ -177,14 +267,14 @@ mod tests {
 
         // 09:00 이후 -> 업데이트
         let now = c.with_ymd_and_hms(2025, 7, 16, 10, 0, 0).unwrap();
-        let service = TimeService { current: now, current_signal: TimeSignal::Update };
+        let service = TimeService { clock: WallClock, current: now, current_signal: TimeSignal::Update };
         let (next, sig) = service.compute_next_time();
         assert_eq!(sig, TimeSignal::Update);
         assert_eq!(next.time().minute(), 1);
 
         // 15:30 이후 -> 다음 거래일
         let friday = c.with_ymd_and_hms(2025, 7, 18, 16, 0, 0).unwrap();
-        let service = TimeService { current: friday, current_signal: TimeSignal::Overnight };
+        let service = TimeService { clock: WallClock, current: friday, current_signal: TimeSignal::Overnight };
         let (next, sig) = service.compute_next_time();
         assert_eq!(sig, TimeSignal::Overnight);
         assert_eq!(next.date_naive().weekday(), Weekday::Mon);
@@ -192,7 +282,8 @@ mod tests {
 
     #[test]
     fn test_time_service_flow() {
-        let mut svc = TimeService::new();
+        let start = Local.with_ymd_and_hms(2025, 7, 16, 10, 0, 0).unwrap();
+        let mut svc = TimeService::with_clock(SimulatedClock::new(start));
         // First advance from now to next event
         let (t1, s1) = svc.advance();
         assert!(t1 >= svc.now());
@@ -204,6 +295,29 @@ mod tests {
         assert!(matches!(s2, TimeSignal::DataPrep | TimeSignal::MarketOpen | TimeSignal::Update | TimeSignal::MarketClose | TimeSignal::Overnight));
     }
 
+    #[test]
+    fn test_simulated_clock_replays_year() {
+        let start = Local.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let end = Local.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let mut svc = TimeService::with_clock(SimulatedClock::new(start));
+
+        let mut open_days = 0;
+        while svc.now() < end {
+            svc.wait_until(svc.now());
+            assert_eq!(svc.clock().now(), svc.now());
+            if svc.now_signal() == TimeSignal::MarketOpen {
+                let date = svc.now().date_naive();
+                assert!(!is_weekend(date) && !is_holiday(date));
+                open_days += 1;
+            }
+            svc.advance();
+        }
+
+        // 2025년 평일 261일 중 휴장일을 제외한 거래일 수
+        let holidays = load_holidays(2025).into_iter().filter(|d| !is_weekend(*d)).count();
+        assert_eq!(open_days, 261 - holidays);
+    }
+
     #[test]
     fn test_holiday_loading() {
         let holidays = load_holidays(2025);
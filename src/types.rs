@@ -3,3 +3,6 @@ pub mod api;
 pub mod data_reader;
 pub mod macros;
 pub mod broker;
+pub mod price;
+pub mod market;
+pub mod order_book;
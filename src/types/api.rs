@@ -1,5 +1,8 @@
+use crate::error::{ApiError, ParseError, StockrsError};
 use chrono::NaiveDateTime;
 use crate::types::trading::AssetInfo;
+use serde_json::Value;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy)]
 pub enum ApiEnv {
@@ -7,14 +10,48 @@ pub enum ApiEnv {
     Paper,
 }
 
-struct Domestic006Output1 {
+/// 잔고조회 응답의 보유 종목 한 줄 (output1)
+#[derive(Debug, Clone)]
+pub struct Domestic006Output1 {
+    /// 종목번호
     pdno: String,
-    pchs_avg_pric: String,
+    /// 종목명
+    prdt_name: String,
+    /// 보유수량
+    hldg_qty: u32,
+    /// 주문가능수량
+    ord_psbl_qty: u32,
+    /// 매입평균가격
+    pchs_avg_pric: f64,
+    /// 매입금액
+    pchs_amt: f64,
+    /// 현재가
+    prpr: f64,
+    /// 평가금액
+    evlu_amt: f64,
+    /// 평가손익금액
+    evlu_pfls_amt: f64,
+    /// 평가손익율 (%)
+    evlu_pfls_rt: f64,
 }
 
-struct Domestic006Output2 {
-    dnca_tot_amt: String,
-    nass_amt: String,
+/// 잔고조회 응답의 계좌 합계 (output2)
+#[derive(Debug, Clone)]
+pub struct Domestic006Output2 {
+    /// 예수금총금액
+    dnca_tot_amt: f64,
+    /// D+2 예수금 (가수도정산금액)
+    prvs_rcdl_excc_amt: f64,
+    /// 유가평가금액
+    scts_evlu_amt: f64,
+    /// 총평가금액
+    tot_evlu_amt: f64,
+    /// 순자산금액
+    nass_amt: f64,
+    /// 매입금액합계금액
+    pchs_amt_smtl_amt: f64,
+    /// 평가손익합계금액
+    evlu_pfls_smtl_amt: f64,
 }
 
 pub struct Domestic006Result {
@@ -23,25 +60,146 @@ pub struct Domestic006Result {
     output2: Domestic006Output2,
 }
 
+/// 응답 JSON에서 숫자 문자열 필드를 읽습니다. (KIS는 숫자도 문자열로 보냄)
+pub(crate) fn parse_field<T>(value: &Value, key: &str) -> Result<T, StockrsError>
+where
+    T: FromStr,
+    StockrsError: From<T::Err>,
+{
+    match &value[key] {
+        Value::String(s) => Ok(s.trim().parse::<T>()?),
+        Value::Number(n) => Ok(n.to_string().parse::<T>()?),
+        _ => Err(ParseError::MissingField(key.to_string()).into()),
+    }
+}
+
+pub(crate) fn string_field(value: &Value, key: &str) -> String {
+    value[key].as_str().unwrap_or_default().trim().to_string()
+}
+
+impl Domestic006Output1 {
+    pub fn from_json(value: &Value) -> Result<Self, StockrsError> {
+        Ok(Self {
+            pdno: string_field(value, "pdno"),
+            prdt_name: string_field(value, "prdt_name"),
+            hldg_qty: parse_field(value, "hldg_qty")?,
+            ord_psbl_qty: parse_field(value, "ord_psbl_qty")?,
+            pchs_avg_pric: parse_field(value, "pchs_avg_pric")?,
+            pchs_amt: parse_field(value, "pchs_amt")?,
+            prpr: parse_field(value, "prpr")?,
+            evlu_amt: parse_field(value, "evlu_amt")?,
+            evlu_pfls_amt: parse_field(value, "evlu_pfls_amt")?,
+            evlu_pfls_rt: parse_field(value, "evlu_pfls_rt")?,
+        })
+    }
+
+    pub fn get_pdno(&self) -> &str { &self.pdno }
+    pub fn get_prdt_name(&self) -> &str { &self.prdt_name }
+    pub fn get_hldg_qty(&self) -> u32 { self.hldg_qty }
+    pub fn get_ord_psbl_qty(&self) -> u32 { self.ord_psbl_qty }
+    pub fn get_pchs_avg_pric(&self) -> f64 { self.pchs_avg_pric }
+    pub fn get_pchs_amt(&self) -> f64 { self.pchs_amt }
+    pub fn get_prpr(&self) -> f64 { self.prpr }
+    pub fn get_evlu_amt(&self) -> f64 { self.evlu_amt }
+    pub fn get_evlu_pfls_amt(&self) -> f64 { self.evlu_pfls_amt }
+    pub fn get_evlu_pfls_rt(&self) -> f64 { self.evlu_pfls_rt }
+}
+
+impl Domestic006Output2 {
+    pub fn from_json(value: &Value) -> Result<Self, StockrsError> {
+        Ok(Self {
+            dnca_tot_amt: parse_field(value, "dnca_tot_amt")?,
+            prvs_rcdl_excc_amt: parse_field(value, "prvs_rcdl_excc_amt")?,
+            scts_evlu_amt: parse_field(value, "scts_evlu_amt")?,
+            tot_evlu_amt: parse_field(value, "tot_evlu_amt")?,
+            nass_amt: parse_field(value, "nass_amt")?,
+            pchs_amt_smtl_amt: parse_field(value, "pchs_amt_smtl_amt")?,
+            evlu_pfls_smtl_amt: parse_field(value, "evlu_pfls_smtl_amt")?,
+        })
+    }
+
+    pub fn get_dnca_tot_amt(&self) -> f64 { self.dnca_tot_amt }
+    pub fn get_prvs_rcdl_excc_amt(&self) -> f64 { self.prvs_rcdl_excc_amt }
+    pub fn get_scts_evlu_amt(&self) -> f64 { self.scts_evlu_amt }
+    pub fn get_tot_evlu_amt(&self) -> f64 { self.tot_evlu_amt }
+    pub fn get_nass_amt(&self) -> f64 { self.nass_amt }
+    pub fn get_pchs_amt_smtl_amt(&self) -> f64 { self.pchs_amt_smtl_amt }
+    pub fn get_evlu_pfls_smtl_amt(&self) -> f64 { self.evlu_pfls_smtl_amt }
+}
+
 impl Domestic006Result {
-    pub fn new(date: NaiveDateTime, output1: Vec<(String,String)>, output2: (String,String)) -> Self {
-        let output1 = output1
-            .into_iter()
-            .map(|(pdno, pchs_avg_pric)| Domestic006Output1 { pdno, pchs_avg_pric })
-            .collect();
-        let output2 = Domestic006Output2 { dnca_tot_amt: output2.0, nass_amt: output2.1 };
-        Self { date, output1, output2 } 
+    pub fn new(date: NaiveDateTime, output1: Vec<Domestic006Output1>, output2: Domestic006Output2) -> Self {
+        Self { date, output1, output2 }
+    }
+
+    /// 연속조회로 받은 모든 페이지의 output1과 마지막 페이지의 output2로 결과를 만듭니다.
+    pub fn from_json(date: NaiveDateTime, output1: &[Value], output2: &Value) -> Result<Self, StockrsError> {
+        let output1 = output1.iter().map(Domestic006Output1::from_json).collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(date, output1, Domestic006Output2::from_json(output2)?))
     }
 
-    pub fn get_pchs_avg_pric(&self, stockcode: String) -> Result<f64, Box<dyn std::error::Error>> {
+    pub fn get_date(&self) -> NaiveDateTime { self.date }
+    pub fn get_holdings(&self) -> &[Domestic006Output1] { &self.output1 }
+    pub fn get_summary(&self) -> &Domestic006Output2 { &self.output2 }
+
+    pub fn get_pchs_avg_pric(&self, stockcode: String) -> Result<f64, StockrsError> {
         let avg = self.output1
             .iter()
             .find(|item| item.pdno == stockcode)
-            .ok_or("종목을 찾을 수 없습니다")?.pchs_avg_pric.parse::<f64>()?;
+            .ok_or_else(|| ApiError::StockNotFound(stockcode.clone()))?
+            .pchs_avg_pric;
         Ok(avg)
     }
 
-    pub fn into(self) -> AssetInfo {
-        AssetInfo::new(self.date, self.output2.nass_amt.parse::<f64>().unwrap())
+    /// 매도 가능 수량 (보유하지 않은 종목이면 0)
+    pub fn get_ord_psbl_qty(&self, stockcode: &str) -> u32 {
+        self.output1
+            .iter()
+            .find(|item| item.pdno == stockcode)
+            .map_or(0, |item| item.ord_psbl_qty)
+    }
+}
+
+impl From<Domestic006Result> for AssetInfo {
+    fn from(result: Domestic006Result) -> Self {
+        AssetInfo::from(&result)
+    }
+}
+
+impl From<&Domestic006Result> for AssetInfo {
+    fn from(result: &Domestic006Result) -> Self {
+        AssetInfo::new(result.date, result.output2.nass_amt)
     }
-} 
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    #[test]
+    fn test_domestic006_from_json() {
+        let holding = |pdno: &str, qty: &str| json!({
+            "pdno": pdno, "prdt_name": "삼성전자", "hldg_qty": qty, "ord_psbl_qty": qty,
+            "pchs_avg_pric": "70000.0000", "pchs_amt": "700000", "prpr": "71000",
+            "evlu_amt": "710000", "evlu_pfls_amt": "10000", "evlu_pfls_rt": "1.43",
+        });
+        let output2 = json!({
+            "dnca_tot_amt": "300000", "prvs_rcdl_excc_amt": "300000", "scts_evlu_amt": "710000",
+            "tot_evlu_amt": "1010000", "nass_amt": "1010000", "pchs_amt_smtl_amt": "700000",
+            "evlu_pfls_smtl_amt": "10000",
+        });
+        let date = NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let result = Domestic006Result::from_json(date, &[holding("005930", "10"), holding("000660", "0")], &output2).unwrap();
+
+        assert_eq!(result.get_holdings().len(), 2);
+        assert_eq!(result.get_ord_psbl_qty("005930"), 10);
+        assert_eq!(result.get_ord_psbl_qty("035420"), 0);
+        assert_eq!(result.get_pchs_avg_pric("005930".to_string()).unwrap(), 70000.0);
+        assert_eq!(AssetInfo::from(result).get_asset(), 1_010_000.0);
+
+        let broken = json!({ "pdno": "005930", "hldg_qty": "ten" });
+        assert!(Domestic006Output1::from_json(&broken).is_err());
+    }
+}
@@ -1,44 +1,215 @@
+use crate::api::db_api::DbApi;
+use crate::error::StockrsError;
 use crate::db_manager::DBManager;
 use crate::types::trading::Trading;
-use chrono::NaiveDateTime;
+use chrono::{Duration, NaiveDateTime};
+use std::sync::Arc;
+use thiserror::Error;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OrderSide {
     Buy,
     Sell,
 }
 
+/// 주문 구분 (KIS `ORD_DVSN`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    /// 지정가
+    Limit,
+    /// 시장가
+    Market,
+    /// 조건부지정가 (장중 지정가, 미체결 시 종가 단일가매매에서 시장가로 전환)
+    ConditionalLimit,
+    /// 최유리지정가 (상대편 최우선 호가로 지정)
+    BestLimit,
+    /// 최우선지정가 (자기편 최우선 호가로 지정)
+    PriorityLimit,
+    /// IOC지정가
+    LimitIoc,
+    /// FOK지정가
+    LimitFok,
+    /// IOC시장가
+    MarketIoc,
+    /// FOK시장가
+    MarketFok,
+    /// IOC최유리
+    BestIoc,
+    /// FOK최유리
+    BestFok,
+}
+
+impl OrderType {
+    /// KIS 주문 API의 `ORD_DVSN` 코드
+    pub fn ord_dvsn(&self) -> &'static str {
+        match self {
+            OrderType::Limit => "00",
+            OrderType::Market => "01",
+            OrderType::ConditionalLimit => "02",
+            OrderType::BestLimit => "03",
+            OrderType::PriorityLimit => "04",
+            OrderType::LimitIoc => "11",
+            OrderType::LimitFok => "12",
+            OrderType::MarketIoc => "13",
+            OrderType::MarketFok => "14",
+            OrderType::BestIoc => "15",
+            OrderType::BestFok => "16",
+        }
+    }
+
+    /// 주문 가격(`Order.price`)을 지정하는 주문인지 여부
+    ///
+    /// 그 외 주문은 가격을 0으로 보내며 거래소가 체결 가격을 정합니다.
+    pub fn has_limit_price(&self) -> bool {
+        matches!(self, OrderType::Limit | OrderType::ConditionalLimit | OrderType::LimitIoc | OrderType::LimitFok)
+    }
+
+    /// 접수 즉시 체결 가능한 수량만 체결하고 잔량을 취소하는 주문인지 여부
+    pub fn is_ioc(&self) -> bool {
+        matches!(self, OrderType::LimitIoc | OrderType::MarketIoc | OrderType::BestIoc)
+    }
+
+    /// 접수 즉시 전량 체결되지 않으면 전량 취소하는 주문인지 여부
+    pub fn is_fok(&self) -> bool {
+        matches!(self, OrderType::LimitFok | OrderType::MarketFok | OrderType::BestFok)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Order {
     pub date: NaiveDateTime,
     pub stockcode: String,
     pub side: OrderSide,
+    pub order_type: OrderType,
     pub quantity: u32,
+    /// 주문 가격 (`order_type.has_limit_price()`가 아니면 무시)
     pub price: f64,
-    pub fee: f64,
     pub strategy: String,
+    /// 미체결 시 자동 취소까지의 시간 (`None`이면 브로커 기본값 사용)
+    pub timeout: Option<Duration>,
+}
+
+/// 주문 상태
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    /// 접수됨 (미체결)
+    Submitted,
+    /// 일부 체결
+    PartiallyFilled,
+    /// 전량 체결
+    Filled,
+    /// 취소됨
+    Cancelled,
+    /// 거부됨
+    Rejected,
+    /// 시한 초과로 취소됨
+    Expired,
+}
+
+impl OrderStatus {
+    /// 더 이상 상태가 바뀌지 않는 최종 상태인지 여부
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderStatus::Filled | OrderStatus::Cancelled | OrderStatus::Rejected | OrderStatus::Expired)
+    }
+}
+
+/// 주문 체결 조회 결과
+#[derive(Debug, Clone, Copy)]
+pub struct OrderInquiry {
+    pub status: OrderStatus,
+    /// 누적 체결 수량
+    pub filled_quantity: u32,
+    /// 누적 체결분의 평균 체결가
+    pub avg_price: f64,
+    /// 마지막 체결 시각 (조회 결과에 없으면 `None`)
+    pub filled_at: Option<NaiveDateTime>,
 }
 
 impl Order {
-    pub fn to_trading(&self) -> Trading {
+    /// 체결 한 건을 이 주문에 연결된 거래 내역으로 변환합니다.
+    ///
+    /// 체결 수량·가격·시각은 체결 정보를 따르고, `fee`는 `FeeModel`로 계산한 이 체결분의 거래비용입니다.
+    pub fn fill_to_trading(&self, fill: &Fill, fee: f64) -> Trading {
         Trading::new(
-            self.date,
+            fill.time,
             self.stockcode.clone(),
             matches!(self.side, OrderSide::Buy),
-            self.quantity,
-            self.price,
-            self.fee,
+            fill.quantity,
+            fill.price,
+            fee,
             self.strategy.clone(),
         )
+        .with_order_id(fill.order_id.clone())
+    }
+}
+
+/// 테스트용 주문 (종목 `005930`, 전략 `test`, 브로커 기본 시한)
+#[cfg(test)]
+pub(crate) fn test_order(date: NaiveDateTime, side: OrderSide, order_type: OrderType, quantity: u32, price: f64) -> Order {
+    Order {
+        date,
+        stockcode: "005930".to_string(),
+        side,
+        order_type,
+        quantity,
+        price,
+        strategy: "test".to_string(),
+        timeout: None,
     }
 }
 
+/// 주문 한 건에 대한 개별 체결
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub order_id: String,
+    /// 이번 체결 수량
+    pub quantity: u32,
+    /// 이번 체결의 평균 체결가
+    pub price: f64,
+    pub time: NaiveDateTime,
+}
+
+/// 주문 제출 전 검증 실패 사유
+#[derive(Debug, Error)]
+pub enum ValidationError {
+    #[error("주문 수량은 0보다 커야 합니다")]
+    InvalidQuantity,
+    #[error("호가단위에 맞지 않는 가격입니다: {price} (호가단위 {tick})")]
+    InvalidTick { price: f64, tick: f64 },
+    #[error("가격제한폭을 벗어난 가격입니다: {price} (허용 범위 {lower} ~ {upper})")]
+    OutOfPriceBand { price: f64, lower: f64, upper: f64 },
+    #[error("주문가능금액이 부족합니다: 필요 {required}, 가능 {available}")]
+    InsufficientCash { required: f64, available: f64 },
+    #[error("매도가능수량이 부족합니다: {stockcode} 주문 {requested}주, 가능 {available}주")]
+    InsufficientHoldings { stockcode: String, requested: u32, available: u32 },
+    #[error("정정할 수 없는 주문입니다: {0}")]
+    NotAmendable(String),
+}
+
 pub enum BrokerType {
     REAL,
     PAPER,
-    DB,
+    /// 백테스트 (주문을 체결시킬 가상 계좌)
+    DB(Arc<DbApi>),
 }
 
 pub trait Broker {
-    fn validate(&self, order: &Order) -> Result<(), Box<dyn std::error::Error>>;
-    fn execute(&self, order: &Order, db: &DBManager) -> Result<(), Box<dyn std::error::Error>>;
+    fn validate(&self, order: &Order) -> Result<(), StockrsError>;
+    /// 주문을 제출하고 체결 추적을 시작합니다. 주문번호를 반환합니다.
+    fn execute(&self, order: &Order, db: &DBManager) -> Result<String, StockrsError>;
+    /// 추적 중인 주문의 체결 상태를 갱신하여 체결분을 기록하고, 시한이 지난 주문을 취소합니다.
+    fn poll(&self, db: &DBManager) -> Result<(), StockrsError>;
+    /// 미체결 주문의 가격 및/또는 잔량을 정정합니다. 정정된 주문의 새 주문번호를 반환합니다.
+    ///
+    /// `quantity`는 정정 후 미체결 잔량으로, 현재 잔량보다 클 수 없습니다.
+    /// 원주문은 `Cancelled`로 끝나고 이후 체결은 새 주문번호로 추적됩니다.
+    /// 정정은 접수되었지만 원주문의 남은 잔량을 취소하지 못하면 새 주문번호를 담은
+    /// `ApiError::RemainderNotCancelled`를 반환하며, 두 주문 모두 계속 추적합니다.
+    fn amend(&self, order_id: &str, price: Option<f64>, quantity: Option<u32>, db: &DBManager) -> Result<String, StockrsError>;
+    /// 남아 있는 미체결 주문을 모두 취소합니다. (장 종료 시 호출)
+    ///
+    /// 일부 주문의 취소가 실패해도 나머지는 취소하며, 실패한 주문번호를 `ApiError::CancelFailed`로 반환합니다.
+    fn expire_all(&self, db: &DBManager) -> Result<(), StockrsError>;
+    /// 주문번호의 현재 상태를 반환합니다.
+    fn status(&self, order_id: &str) -> Option<OrderStatus>;
 }
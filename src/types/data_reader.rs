@@ -1,12 +1,25 @@
-use crate::types::trading::AssetInfo;
+use crate::api::db_api::DbApi;
+use crate::error::StockrsError;
+use crate::types::trading::{AssetInfo, Holding};
+use std::sync::Arc;
 
 pub enum DataReaderType {
-    DB,
+    /// 백테스트 (조회할 가상 계좌)
+    DB(Arc<DbApi>),
     PAPER,
     REAL,
 }
 
 pub trait DataReader {
-    fn get_asset_info(&self) -> Result<AssetInfo, Box<dyn std::error::Error>>;
-    fn get_avg_price(&self, stockcode: String) -> Result<f64, Box<dyn std::error::Error>>;
+    fn get_asset_info(&self) -> Result<AssetInfo, StockrsError>;
+    fn get_avg_price(&self, stockcode: String) -> Result<f64, StockrsError>;
+    /// 보유 종목 (보유수량이 0인 종목 제외, 종목코드 순)
+    fn get_holdings(&self) -> Result<Vec<Holding>, StockrsError>;
+    /// 주문가능 예수금 (D+2 예수금)
+    fn get_orderable_cash(&self) -> Result<f64, StockrsError>;
+    /// 종목의 현재가
+    fn get_current_price(&self, stockcode: &str) -> Result<f64, StockrsError>;
+
+    /// 체결 등으로 계좌 상태가 바뀌었음을 알립니다. 조회 결과를 캐시하는 리더는 다음 조회 때 새로 읽습니다.
+    fn invalidate(&self) {}
 }
@@ -0,0 +1,27 @@
+use serde::Deserialize;
+
+/// 상장 시장 구분
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Market {
+    KOSPI,
+    KOSDAQ,
+    /// 상장지수펀드 (상장 시장과 무관하게 호가단위와 세금이 따로 적용됨)
+    ETF,
+}
+
+/// 주문 검증에 필요한 종목의 당일 가격 정보
+#[derive(Debug, Clone, Copy)]
+pub struct PriceInfo {
+    market: Market,
+    /// 기준가 (전일 종가)
+    prev_close: f64,
+}
+
+impl PriceInfo {
+    pub fn new(market: Market, prev_close: f64) -> Self {
+        Self { market, prev_close }
+    }
+
+    pub fn get_market(&self) -> Market { self.market }
+    pub fn get_prev_close(&self) -> f64 { self.prev_close }
+}
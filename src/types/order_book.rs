@@ -0,0 +1,134 @@
+use crate::types::broker::OrderSide;
+use chrono::NaiveDateTime;
+
+/// 호가 한 단계
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookLevel {
+    pub price: f64,
+    /// 잔량
+    pub quantity: u64,
+}
+
+/// 호가를 따라 체결시켰을 때의 예상 체결
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FillEstimate {
+    pub quantity: u32,
+    pub avg_price: f64,
+}
+
+/// 종목의 매도/매수 10단계 호가
+///
+/// 매도호가(`asks`)는 낮은 가격부터, 매수호가(`bids`)는 높은 가격부터 정렬되며 잔량이 없는 단계는 제외합니다.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderBook {
+    stockcode: String,
+    time: NaiveDateTime,
+    asks: Vec<BookLevel>,
+    bids: Vec<BookLevel>,
+}
+
+impl OrderBook {
+    pub fn new(stockcode: String, time: NaiveDateTime, mut asks: Vec<BookLevel>, mut bids: Vec<BookLevel>) -> Self {
+        asks.retain(|level| level.quantity > 0 && level.price > 0.0);
+        bids.retain(|level| level.quantity > 0 && level.price > 0.0);
+        asks.sort_by(|a, b| a.price.total_cmp(&b.price));
+        bids.sort_by(|a, b| b.price.total_cmp(&a.price));
+        Self { stockcode, time, asks, bids }
+    }
+
+    pub fn get_stockcode(&self) -> &str { &self.stockcode }
+    pub fn get_time(&self) -> NaiveDateTime { self.time }
+    pub fn get_asks(&self) -> &[BookLevel] { &self.asks }
+    pub fn get_bids(&self) -> &[BookLevel] { &self.bids }
+
+    /// 최우선 매수호가
+    pub fn best_bid(&self) -> Option<BookLevel> {
+        self.bids.first().copied()
+    }
+
+    /// 최우선 매도호가
+    pub fn best_ask(&self) -> Option<BookLevel> {
+        self.asks.first().copied()
+    }
+
+    /// 최우선 매도호가 - 최우선 매수호가
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+
+    pub fn mid_price(&self) -> Option<f64> {
+        Some((self.best_ask()?.price + self.best_bid()?.price) / 2.0)
+    }
+
+    /// 한쪽 호가의 상위 `levels`단계 잔량 합 (`Buy`는 매수호가, `Sell`은 매도호가)
+    pub fn depth(&self, side: OrderSide, levels: usize) -> u64 {
+        let book = match side {
+            OrderSide::Buy => &self.bids,
+            OrderSide::Sell => &self.asks,
+        };
+        book.iter().take(levels).map(|level| level.quantity).sum()
+    }
+
+    /// 상위 `levels`단계의 잔량 불균형 (매수 - 매도) / (매수 + 매도), -1 ~ 1
+    pub fn imbalance(&self, levels: usize) -> Option<f64> {
+        let bid = self.depth(OrderSide::Buy, levels) as f64;
+        let ask = self.depth(OrderSide::Sell, levels) as f64;
+        (bid + ask > 0.0).then(|| (bid - ask) / (bid + ask))
+    }
+
+    /// `side` 주문 `quantity`주를 상대편 호가부터 차례로 체결시켰을 때의 예상 체결
+    ///
+    /// `limit`이 있으면 그 가격보다 불리한 호가는 쓰지 않습니다. 체결 가능한 잔량이 없으면 `None`입니다.
+    pub fn estimate_fill(&self, side: OrderSide, quantity: u32, limit: Option<f64>) -> Option<FillEstimate> {
+        let book = match side {
+            OrderSide::Buy => &self.asks,
+            OrderSide::Sell => &self.bids,
+        };
+        let acceptable = |price: f64| match (side, limit) {
+            (_, None) => true,
+            (OrderSide::Buy, Some(limit)) => price <= limit,
+            (OrderSide::Sell, Some(limit)) => price >= limit,
+        };
+
+        let (mut filled, mut amount) = (0u32, 0.0);
+        for level in book.iter().take_while(|level| acceptable(level.price)) {
+            let take = (quantity - filled).min(u32::try_from(level.quantity).unwrap_or(u32::MAX));
+            filled += take;
+            amount += take as f64 * level.price;
+            if filled == quantity {
+                break;
+            }
+        }
+        (filled > 0).then(|| FillEstimate { quantity: filled, avg_price: amount / filled as f64 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_book_metrics_and_fill_estimate() {
+        let level = |price, quantity| BookLevel { price, quantity };
+        let book = OrderBook::new(
+            "005930".to_string(),
+            NaiveDate::from_ymd_opt(2025, 7, 16).unwrap().and_hms_opt(9, 0, 0).unwrap(),
+            vec![level(70200.0, 50), level(70100.0, 10), level(70300.0, 0)],
+            vec![level(69900.0, 20), level(70000.0, 30)],
+        );
+        assert_eq!(book.best_ask(), Some(level(70100.0, 10)));
+        assert_eq!(book.best_bid(), Some(level(70000.0, 30)));
+        assert_eq!(book.spread(), Some(100.0));
+        assert_eq!(book.mid_price(), Some(70050.0));
+        assert_eq!(book.depth(OrderSide::Sell, 10), 60);
+        assert_eq!(book.imbalance(1), Some(0.5));
+
+        // 10주는 70,100원, 나머지 5주는 70,200원
+        let estimate = book.estimate_fill(OrderSide::Buy, 15, None).unwrap();
+        assert_eq!((estimate.quantity, estimate.avg_price.round()), (15, 70133.0));
+        assert_eq!(book.estimate_fill(OrderSide::Buy, 15, Some(70100.0)).unwrap().quantity, 10);
+        assert_eq!(book.estimate_fill(OrderSide::Sell, 100, None).unwrap().quantity, 50);
+        assert!(book.estimate_fill(OrderSide::Sell, 1, Some(70100.0)).is_none());
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+
+/// 분봉/일봉 시세 (OHLCV)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bar {
+    time: NaiveDateTime,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: u64,
+}
+
+impl Bar {
+    pub fn new(time: NaiveDateTime, open: f64, high: f64, low: f64, close: f64, volume: u64) -> Self {
+        Self { time, open, high, low, close, volume }
+    }
+
+    pub fn get_time(&self) -> NaiveDateTime { self.time }
+    pub fn get_open(&self) -> f64 { self.open }
+    pub fn get_high(&self) -> f64 { self.high }
+    pub fn get_low(&self) -> f64 { self.low }
+    pub fn get_close(&self) -> f64 { self.close }
+    pub fn get_volume(&self) -> u64 { self.volume }
+}
+
+/// 분봉 주기
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BarInterval {
+    Minute1,
+    Minute5,
+}
+
+impl BarInterval {
+    pub fn duration(&self) -> Duration {
+        match self {
+            BarInterval::Minute1 => Duration::minutes(1),
+            BarInterval::Minute5 => Duration::minutes(5),
+        }
+    }
+
+    /// `time`이 속한 봉의 시작 시각 (자정부터 주기 단위로 내림하므로 09:00 장 시작에 맞춰짐)
+    pub fn bucket_start(&self, time: NaiveDateTime) -> NaiveDateTime {
+        let minutes = self.duration().num_minutes();
+        let since_midnight = (time - time.date().and_time(NaiveTime::MIN)).num_minutes();
+        time.date().and_time(NaiveTime::MIN) + Duration::minutes(since_midnight / minutes * minutes)
+    }
+}
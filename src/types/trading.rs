@@ -8,6 +8,7 @@ pub struct Trading {
     price: f64,
     fee: f64,
     strategy: String,
+    order_id: Option<String>,
 }
 
 pub struct TradingResult {
@@ -22,13 +23,44 @@ pub struct TradingResult {
     avg_price: f64,
     profit: f64,
     roi: f64,
+    order_id: Option<String>,
 }
 
+/// `trading` 테이블 한 행의 값 순서
+/// (date, time, stockcode, buy_or_sell, quantity, price, fee, strategy, avg_price, profit, roi, order_id)
+pub type TradingDbRow = (
+    String, String, String, String, u32, f64, f64, String, f64, f64, f64, Option<String>
+);
+
+#[derive(Debug, Clone, Copy)]
 pub struct AssetInfo {
     date: NaiveDateTime,
     asset: f64,
 }
 
+/// 보유 종목 정보
+#[derive(Debug, Clone)]
+pub struct Holding {
+    stockcode: String,
+    quantity: u32,
+    /// 주문가능수량 (미체결 매도 주문에 묶인 수량 제외)
+    orderable_quantity: u32,
+    avg_price: f64,
+}
+
+/// 체결로 누적한 종목별 포지션 (`DBManager`의 `positions` 테이블)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    stockcode: String,
+    quantity: u32,
+    /// 평균매입가 (수수료 제외)
+    avg_price: f64,
+    /// 실현손익 누계 (매수·매도 수수료와 세금 포함)
+    realized_pnl: f64,
+    /// 최초 진입 시각 (보유수량이 0이면 `None`, 다시 매수하면 새로 정해짐)
+    first_entry: Option<NaiveDateTime>,
+}
+
 /*
 ------------------- impl -------------------
 */
@@ -42,9 +74,44 @@ impl AssetInfo {
     pub fn get_asset(&self) -> f64 { self.asset }
 }
 
+impl Holding {
+    pub fn new(stockcode: String, quantity: u32, avg_price: f64) -> Self {
+        Self { stockcode, quantity, orderable_quantity: quantity, avg_price }
+    }
+
+    /// 주문가능수량을 지정합니다. (기본값: 보유수량)
+    pub fn with_orderable_quantity(mut self, orderable_quantity: u32) -> Self {
+        self.orderable_quantity = orderable_quantity;
+        self
+    }
+
+    pub fn get_stockcode(&self) -> &str { &self.stockcode }
+    pub fn get_quantity(&self) -> u32 { self.quantity }
+    pub fn get_orderable_quantity(&self) -> u32 { self.orderable_quantity }
+    pub fn get_avg_price(&self) -> f64 { self.avg_price }
+}
+
+impl Position {
+    pub fn new(stockcode: String, quantity: u32, avg_price: f64, realized_pnl: f64, first_entry: Option<NaiveDateTime>) -> Self {
+        Self { stockcode, quantity, avg_price, realized_pnl, first_entry }
+    }
+
+    pub fn get_stockcode(&self) -> &str { &self.stockcode }
+    pub fn get_quantity(&self) -> u32 { self.quantity }
+    pub fn get_avg_price(&self) -> f64 { self.avg_price }
+    pub fn get_realized_pnl(&self) -> f64 { self.realized_pnl }
+    pub fn get_first_entry(&self) -> Option<NaiveDateTime> { self.first_entry }
+}
+
 impl Trading {
     pub fn new(date: NaiveDateTime, stockcode: String, buy_or_sell: bool, quantity: u32, price: f64, fee: f64, strategy: String) -> Self {
-        Self { date, stockcode, buy_or_sell, quantity, price, fee, strategy }
+        Self { date, stockcode, buy_or_sell, quantity, price, fee, strategy, order_id: None }
+    }
+
+    /// 이 거래를 발생시킨 주문번호를 연결합니다.
+    pub fn with_order_id(mut self, order_id: String) -> Self {
+        self.order_id = Some(order_id);
+        self
     }
 
     pub fn get_date(&self) -> NaiveDateTime { self.date }
@@ -54,6 +121,7 @@ impl Trading {
     pub fn get_price(&self) -> f64 { self.price }
     pub fn get_fee(&self) -> f64 { self.fee }
     pub fn get_strategy(&self) -> &str { &self.strategy }
+    pub fn get_order_id(&self) -> Option<&str> { self.order_id.as_deref() }
 
     pub fn to_trading_result(&self, avg_price: f64) -> TradingResult {
         let profit = match self.buy_or_sell {
@@ -61,13 +129,23 @@ impl Trading {
             false => (self.price - avg_price) * self.quantity as f64 - self.fee as f64, // 매도면 손익금 = (매도가 - 평균매입가) * 수량 - 수수료
         };
         let roi = profit / (avg_price * self.quantity as f64) * 100.0;
-        TradingResult::new(self.date.date(), self.date.time(), self.stockcode.clone(), self.buy_or_sell, self.quantity, self.price, self.fee, self.strategy.clone(), avg_price, profit, roi)
+        let result = TradingResult::new(self.date.date(), self.date.time(), self.stockcode.clone(), self.buy_or_sell, self.quantity, self.price, self.fee, self.strategy.clone(), avg_price, profit, roi);
+        match &self.order_id {
+            Some(order_id) => result.with_order_id(order_id.clone()),
+            None => result,
+        }
     }
 }
 
 impl TradingResult {
     pub fn new(date: NaiveDate, time: NaiveTime, stockcode: String, buy_or_sell: bool, quantity: u32, price: f64, fee: f64, strategy: String, avg_price: f64, profit: f64, roi: f64) -> Self {
-        Self { date, time, stockcode, buy_or_sell, quantity, price, fee, strategy, avg_price, profit, roi }
+        Self { date, time, stockcode, buy_or_sell, quantity, price, fee, strategy, avg_price, profit, roi, order_id: None }
+    }
+
+    /// 이 거래를 발생시킨 주문번호를 연결합니다.
+    pub fn with_order_id(mut self, order_id: String) -> Self {
+        self.order_id = Some(order_id);
+        self
     }
 
     // Getter methods
@@ -82,6 +160,7 @@ impl TradingResult {
     pub fn get_avg_price(&self) -> f64 { self.avg_price }
     pub fn get_profit(&self) -> f64 { self.profit }
     pub fn get_roi(&self) -> f64 { self.roi }
+    pub fn get_order_id(&self) -> Option<&str> { self.order_id.as_deref() }
 
     // Convert stockcode to string (now just returns the string directly)
     pub fn get_stockcode_string(&self) -> String {
@@ -94,9 +173,7 @@ impl TradingResult {
     }
 
     // Return tuple for database insertion
-    pub fn to_db_tuple(&self) -> (
-        String, String, String, String, u32, f64, f64, String, f64, f64, f64
-    ) {
+    pub fn to_db_tuple(&self) -> TradingDbRow {
         (
             self.get_date().to_string(),
             self.get_time().to_string(),
@@ -108,7 +185,8 @@ impl TradingResult {
             self.get_strategy().to_string(),
             self.get_avg_price(),
             self.get_profit(),
-            self.get_roi()
+            self.get_roi(),
+            self.order_id.clone()
         )
     }
 } 
\ No newline at end of file